@@ -0,0 +1,232 @@
+// audio.rs
+
+use raylib::prelude::*;
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink, SpatialSink};
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::player::Player;
+use crate::sprites::Sprite;
+
+// Distancia de cada oído al centro de la cabeza, en bloques
+const EAR_OFFSET: f32 = 0.2;
+// Duración del crossfade entre pistas de música, en segundos
+const CROSSFADE_SECS: f32 = 1.5;
+
+struct Emitter {
+    sink: SpatialSink,
+    pos: Vector2,
+    sprite: Option<usize>, // índice del sprite al que sigue, si tiene
+}
+
+struct Track {
+    sink: Sink,
+    gain: f32, // 0.0 a 1.0 según el avance del crossfade
+}
+
+pub struct Audio {
+    stream: Option<OutputStream>, // None si no hay dispositivo de audio
+    block_size: f32,
+    left_ear: [f32; 3],
+    right_ear: [f32; 3],
+    emitters: Vec<Emitter>,
+    music: Option<Track>,
+    fading_out: Vec<Track>,
+    playlist: Vec<String>,
+    playlist_index: usize,
+    music_volume: f32,
+    effects_volume: f32,
+    muted: bool,
+}
+
+impl Audio {
+    pub fn new(block_size: usize) -> Self {
+        let stream = match OutputStreamBuilder::open_default_stream() {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                eprintln!("No se pudo abrir el dispositivo de audio: {}", e);
+                None
+            }
+        };
+
+        Audio {
+            stream,
+            block_size: block_size as f32,
+            left_ear: [-EAR_OFFSET, 0.0, 0.0],
+            right_ear: [EAR_OFFSET, 0.0, 0.0],
+            emitters: Vec::new(),
+            music: None,
+            fading_out: Vec::new(),
+            playlist: Vec::new(),
+            playlist_index: 0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            muted: false,
+        }
+    }
+
+    // Un bloque del laberinto equivale a una unidad de distancia para rodio,
+    // así el volumen cae con el cuadrado de la distancia en bloques
+    fn to_audio_space(&self, pos: Vector2) -> [f32; 3] {
+        [pos.x / self.block_size, pos.y / self.block_size, 0.0]
+    }
+
+    fn spatial_sink(&self, pos: Vector2) -> Option<SpatialSink> {
+        let stream = self.stream.as_ref()?;
+        let sink = SpatialSink::connect_new(
+            stream.mixer(),
+            self.to_audio_space(pos),
+            self.left_ear,
+            self.right_ear,
+        );
+        sink.set_volume(self.effects_volume);
+        Some(sink)
+    }
+
+    // Reproduce un sonido una sola vez en una posición fija del mundo
+    pub fn play_at(&mut self, path: &str, pos: Vector2) {
+        if self.stream.is_none() { return; }
+        let Some(file) = open_sound(path) else { return };
+        match Decoder::try_from(file) {
+            Ok(source) => {
+                let Some(sink) = self.spatial_sink(pos) else { return };
+                sink.append(source);
+                self.emitters.push(Emitter { sink, pos, sprite: None });
+            }
+            Err(e) => eprintln!("No se pudo decodificar {}: {}", path, e),
+        }
+    }
+
+    // Sonido en bucle que sigue al sprite hasta que se recolecta
+    pub fn attach_to_sprite(&mut self, path: &str, index: usize, sprite: &Sprite) {
+        if self.stream.is_none() { return; }
+        let Some(file) = open_sound(path) else { return };
+        match Decoder::new_looped(BufReader::new(file)) {
+            Ok(source) => {
+                let Some(sink) = self.spatial_sink(sprite.pos) else { return };
+                sink.append(source);
+                self.emitters.push(Emitter { sink, pos: sprite.pos, sprite: Some(index) });
+            }
+            Err(e) => eprintln!("No se pudo decodificar {}: {}", path, e),
+        }
+    }
+
+    // Se llama una vez por frame: mueve los oídos con el jugador y los
+    // emisores con sus sprites, y descarta los sonidos que ya terminaron
+    pub fn update(&mut self, player: &Player, sprites: &[Sprite]) {
+        let right = Vector2::new(-player.a.sin(), player.a.cos());
+        let offset = right * (EAR_OFFSET * self.block_size);
+        self.left_ear = self.to_audio_space(player.pos - offset);
+        self.right_ear = self.to_audio_space(player.pos + offset);
+
+        self.emitters.retain_mut(|emitter| {
+            if let Some(index) = emitter.sprite {
+                match sprites.get(index) {
+                    Some(sprite) if !sprite.collected => emitter.pos = sprite.pos,
+                    _ => {
+                        emitter.sink.stop();
+                        return false;
+                    }
+                }
+            }
+            !emitter.sink.empty()
+        });
+
+        for emitter in &self.emitters {
+            emitter.sink.set_emitter_position(self.to_audio_space(emitter.pos));
+            emitter.sink.set_left_ear_position(self.left_ear);
+            emitter.sink.set_right_ear_position(self.right_ear);
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for emitter in self.emitters.drain(..) {
+            emitter.sink.stop();
+        }
+    }
+
+    // Cambia a otra lista de reproducción con crossfade. Si ya está sonando
+    // la misma lista no hace nada, así se puede llamar en cada pantalla.
+    pub fn play_music(&mut self, playlist: &[&str]) {
+        if self.playlist.iter().map(String::as_str).eq(playlist.iter().copied()) {
+            return;
+        }
+        self.playlist = playlist.iter().map(|path| path.to_string()).collect();
+        self.playlist_index = 0;
+        self.start_track();
+    }
+
+    // Empieza la pista actual de la lista (o la siguiente que se pueda abrir)
+    // y manda la anterior a desvanecerse
+    fn start_track(&mut self) {
+        if let Some(track) = self.music.take() {
+            self.fading_out.push(track);
+        }
+        let Some(stream) = self.stream.as_ref() else { return };
+
+        for attempt in 0..self.playlist.len() {
+            let index = (self.playlist_index + attempt) % self.playlist.len();
+            let path = &self.playlist[index];
+            let Some(file) = open_sound(path) else { continue };
+            match Decoder::try_from(file) {
+                Ok(source) => {
+                    let sink = Sink::connect_new(stream.mixer());
+                    sink.set_volume(0.0);
+                    sink.append(source);
+                    self.music = Some(Track { sink, gain: 0.0 });
+                    self.playlist_index = index;
+                    return;
+                }
+                Err(e) => eprintln!("No se pudo decodificar {}: {}", path, e),
+            }
+        }
+    }
+
+    // Avanza los crossfades; se llama una vez por frame en todas las pantallas
+    pub fn update_music(&mut self, dt: f32) {
+        let step = dt / CROSSFADE_SECS;
+        let volume = if self.muted { 0.0 } else { self.music_volume };
+
+        let mut finished = false;
+        if let Some(track) = &mut self.music {
+            track.gain = (track.gain + step).min(1.0);
+            track.sink.set_volume(track.gain * volume);
+            finished = track.sink.empty();
+        }
+        if finished {
+            self.music = None;
+            self.playlist_index = (self.playlist_index + 1) % self.playlist.len();
+            self.start_track();
+        }
+
+        self.fading_out.retain_mut(|track| {
+            track.gain -= step;
+            track.sink.set_volume(track.gain.max(0.0) * volume);
+            track.gain > 0.0
+        });
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    // Volúmenes de 0.0 a 1.0; la música lo toma en el siguiente
+    // `update_music` y los sonidos que ya están sonando, en el momento
+    pub fn set_volumes(&mut self, music: f32, effects: f32) {
+        self.music_volume = music;
+        self.effects_volume = effects;
+        for emitter in &self.emitters {
+            emitter.sink.set_volume(effects);
+        }
+    }
+}
+
+fn open_sound(path: &str) -> Option<File> {
+    match File::open(path) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("No se pudo abrir el sonido {}: {}", path, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// camera.rs
+//
+// Efectos de cámara: el balanceo de la cabeza al caminar y las sacudidas
+// (al recibir daño, al abrir una puerta...). No mueven al jugador; se
+// juntan en un `ViewOffset` que se suma a la vista al dibujar, así que
+// paredes, piso y sprites se corren igual. Se pueden apagar desde las
+// opciones.
+
+use std::f32::consts::PI;
+
+const BOB_RATE: f32 = 2.0 * PI * 1.8; // pasos por segundo a la velocidad máxima
+const BOB_HEIGHT: f32 = 0.015; // alturas de pared
+const BOB_SWAY: f32 = 0.004; // radianes de lado a lado
+const BOB_SMOOTHING: f32 = 8.0; // qué tan rápido empieza y se calma
+
+// Con intensidad 1 la vista se corre hasta esto
+const SHAKE_ANGLE: f32 = 0.03; // radianes
+const SHAKE_PITCH: f32 = 0.06; // fracción de media pantalla
+
+// Lo que los efectos le suman a la vista del jugador en este frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ViewOffset {
+    pub angle: f32,
+    pub pitch: f32,
+    pub eye: f32,
+}
+
+struct Shake {
+    intensity: f32,
+    duration: f32,
+    remaining: f32,
+    falloff: f32,
+}
+
+#[derive(Default)]
+pub struct CameraEffects {
+    bob_phase: f32,
+    bob_amount: f32, // 0 quieto, 1 a la velocidad máxima
+    shakes: Vec<Shake>,
+    time: f32,
+}
+
+impl CameraEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Sacude la cámara con `intensity` (1 es fuerte) durante `duration`
+    // segundos. Se apaga de a poco según `falloff`, el exponente de lo que
+    // falta: 1 es lineal, 2 cuadrática y más alto se calma antes. Las
+    // sacudidas que se pisan se suman hasta intensidad 1.
+    pub fn shake(&mut self, intensity: f32, duration: f32, falloff: f32) {
+        if duration > 0.0 {
+            self.shakes.push(Shake { intensity, duration, remaining: duration, falloff: falloff.max(0.0) });
+        }
+    }
+
+    // `speed` es la velocidad del jugador como fracción de la máxima al
+    // caminar; en el aire la cabeza no se balancea
+    pub fn update(&mut self, dt: f32, speed: f32, grounded: bool) {
+        self.time += dt;
+
+        let target = if grounded { speed.clamp(0.0, 1.5) } else { 0.0 };
+        self.bob_amount += (target - self.bob_amount) * (BOB_SMOOTHING * dt).min(1.0);
+        self.bob_phase = (self.bob_phase + BOB_RATE * self.bob_amount * dt) % (2.0 * PI);
+
+        for shake in &mut self.shakes {
+            shake.remaining -= dt;
+        }
+        self.shakes.retain(|shake| shake.remaining > 0.0);
+    }
+
+    pub fn offset(&self) -> ViewOffset {
+        let bob = self.bob_amount;
+        let mut offset = ViewOffset {
+            angle: BOB_SWAY * bob * self.bob_phase.sin(),
+            pitch: 0.0,
+            eye: BOB_HEIGHT * bob * (2.0 * self.bob_phase).sin(),
+        };
+
+        // Ruido con senos de frecuencias que no coinciden, para que no se
+        // note un patrón
+        let strength: f32 = self
+            .shakes
+            .iter()
+            .map(|shake| shake.intensity * (shake.remaining / shake.duration).powf(shake.falloff))
+            .sum::<f32>()
+            .min(1.0);
+        if strength > 0.0 {
+            let t = self.time;
+            offset.angle += SHAKE_ANGLE * strength * ((t * 37.0).sin() + 0.5 * (t * 71.0 + 1.7).sin()) / 1.5;
+            offset.pitch += SHAKE_PITCH * strength * ((t * 53.0 + 0.4).sin() + 0.5 * (t * 89.0).sin()) / 1.5;
+        }
+        offset
+    }
+}
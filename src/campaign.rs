@@ -0,0 +1,111 @@
+// campaign.rs
+//
+// La campaña es la lista ordenada de niveles del menú, leída de un archivo
+// con un nivel por línea (y comentarios con `#`). Un nivel se desbloquea
+// al completar el anterior; el progreso (niveles completados y su mejor
+// tiempo) se guarda entre partidas en el directorio de datos del usuario.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::maze::load_maze;
+use crate::storage::{user_file, write_user_file, UserDir};
+
+pub struct CampaignLevel {
+    pub file: String,
+    pub name: String, // el `name` de la cabecera, o el archivo si no tiene
+}
+
+pub struct Campaign {
+    pub levels: Vec<CampaignLevel>,
+}
+
+impl Campaign {
+    pub fn load(path: &str) -> Result<Campaign, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("no se pudo leer {}: {}", path, e))?;
+        let levels = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .map(|file| {
+                let name = match load_maze(file) {
+                    Ok(level) if !level.meta.name.is_empty() => level.meta.name,
+                    _ => file.to_string(),
+                };
+                CampaignLevel { file: file.to_string(), name }
+            })
+            .collect();
+        Ok(Campaign { levels })
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.levels.iter().map(|level| level.file.as_str())
+    }
+
+    // Nivel que sigue a `file`: el `next` de su cabecera si lo tiene y si no
+    // el siguiente de la lista
+    pub fn next_level(&self, file: &str, header_next: Option<&str>) -> Option<String> {
+        if let Some(next) = header_next {
+            return Some(next.to_string());
+        }
+        let index = self.levels.iter().position(|level| level.file == file)?;
+        self.levels.get(index + 1).map(|level| level.file.clone())
+    }
+
+    pub fn is_unlocked(&self, index: usize, progress: &Progress) -> bool {
+        index == 0 || self.levels.get(index - 1).is_some_and(|previous| progress.is_completed(&previous.file))
+    }
+}
+
+pub struct Progress {
+    best_times: HashMap<String, f32>, // archivo del nivel -> mejor tiempo en segundos
+    path: Option<PathBuf>,
+}
+
+impl Progress {
+    // Si no hay progreso guardado (o no se puede leer) se empieza de cero
+    pub fn load() -> Progress {
+        let path = progress_path();
+        let mut best_times = HashMap::new();
+        if let Some(text) = path.as_ref().and_then(|path| fs::read_to_string(path).ok()) {
+            for line in text.lines() {
+                let line = line.split('#').next().unwrap_or("").trim();
+                let Some((file, time)) = line.rsplit_once('=') else { continue };
+                if let Ok(time) = time.trim().parse::<f32>() {
+                    best_times.insert(file.trim().to_string(), time);
+                }
+            }
+        }
+        Progress { best_times, path }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = &self.path else { return };
+        let mut files: Vec<_> = self.best_times.iter().collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+        let mut text = String::from("# Progreso de la campaña: nivel = mejor tiempo en segundos\n");
+        for (file, time) in files {
+            text.push_str(&format!("{} = {:.1}\n", file, time));
+        }
+
+        write_user_file(path, &text, "el progreso");
+    }
+
+    pub fn complete(&mut self, file: &str, time: f32) {
+        let best = self.best_times.entry(file.to_string()).or_insert(time);
+        *best = best.min(time);
+    }
+
+    pub fn is_completed(&self, file: &str) -> bool {
+        self.best_times.contains_key(file)
+    }
+
+    pub fn best_time(&self, file: &str) -> Option<f32> {
+        self.best_times.get(file).copied()
+    }
+}
+
+fn progress_path() -> Option<PathBuf> {
+    user_file(UserDir::Data, "progress.txt")
+}
@@ -1,12 +1,13 @@
   use raylib::color::Color;
 
+  use crate::cell::Cell;
   use crate::framebuffer::Framebuffer;
   use crate::maze::Maze;
   use crate::player::Player;
 
   pub struct Intersect {
     pub distance: f32,
-    pub impact: char,
+    pub impact: Cell,
     pub tx: usize,
   }
 
@@ -31,7 +32,16 @@
       let i = x / block_size;
       let j = y / block_size;
 
-      if maze[j][i] != ' ' {
+      // Fuera del mapa: se corta el rayo como si hubiera pared
+      let Some(&cell) = maze.get(j).and_then(|row| row.get(i)) else {
+        return Intersect{
+          distance: d,
+          impact: Cell::Wall { texture: '+' },
+          tx: 0,
+        };
+      };
+
+      if cell.is_solid() {
         let hitx = x - i*block_size;
         let hity = y - j*block_size;
         let mut maxhit = hity;
@@ -44,8 +54,8 @@
 
         return Intersect{
           distance: d,
-          impact: maze[j][i],
-          tx: tx
+          impact: cell,
+          tx
         };
       }
 
@@ -0,0 +1,141 @@
+// cell.rs
+
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+// Todo lo que el motor necesita saber de una celda sale de aquí: si bloquea
+// el paso y los rayos, con qué textura se dibuja y cómo se ve en el minimapa.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Wall { texture: char },
+    Door,
+    Spawn { facing: Option<f32> }, // ángulo inicial; None usa el de siempre
+    Pickup { kind: char },
+    Enemy { kind: char }, // punto de aparición de un enemigo; se camina como pasillo
+    Hazard { kind: HazardKind }, // se puede pisar, pero quita vida
+    Exit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HazardKind {
+    Lava,
+    Spikes,
+}
+
+impl HazardKind {
+    pub fn from_name(name: &str) -> Option<HazardKind> {
+        match name {
+            "lava" => Some(HazardKind::Lava),
+            "spikes" | "pinchos" => Some(HazardKind::Spikes),
+            _ => None,
+        }
+    }
+
+    // Vida por segundo mientras el jugador está encima
+    pub fn damage(&self) -> f32 {
+        match self {
+            HazardKind::Lava => 50.0,
+            HazardKind::Spikes => 20.0,
+        }
+    }
+
+    // Color del piso de la celda y del minimapa
+    pub fn color(&self) -> Color {
+        match self {
+            HazardKind::Lava => Color::ORANGE,
+            HazardKind::Spikes => Color::GRAY,
+        }
+    }
+}
+
+impl Cell {
+    pub fn is_solid(&self) -> bool {
+        matches!(self, Cell::Wall { .. } | Cell::Door)
+    }
+
+    // Clave en el TextureManager para las celdas que se dibujan como pared
+    pub fn texture(&self) -> Option<char> {
+        match self {
+            Cell::Wall { texture } => Some(*texture),
+            Cell::Door => Some('D'),
+            _ => None,
+        }
+    }
+
+    pub fn minimap_color(&self) -> Option<Color> {
+        match self {
+            Cell::Wall { texture: '+' } => Some(Color::BLUEVIOLET),
+            Cell::Wall { .. } => Some(Color::VIOLET),
+            Cell::Door => Some(Color::BROWN),
+            Cell::Exit => Some(Color::GREEN),
+            Cell::Hazard { kind } => Some(kind.color()),
+            // Los coleccionables y enemigos se marcan aparte con sus sprites
+            Cell::Empty | Cell::Spawn { .. } | Cell::Pickup { .. } | Cell::Enemy { .. } => None,
+        }
+    }
+}
+
+pub const DEFAULT_SPAWN: &str = "inicio";
+
+// Traduce los símbolos del archivo de nivel a celdas
+pub struct Legend {
+    symbols: HashMap<char, Cell>,
+    // Nombre de cada símbolo de spawn, y para las salidas el spawn del
+    // siguiente nivel por el que se entra
+    names: HashMap<char, String>,
+}
+
+impl Legend {
+    // La leyenda de los laberintos ASCII clásicos (maze.txt, maze2.txt)
+    pub fn classic() -> Self {
+        let mut symbols = HashMap::from([
+            (' ', Cell::Empty),
+            ('+', Cell::Wall { texture: '+' }),
+            ('-', Cell::Wall { texture: '-' }),
+            ('|', Cell::Wall { texture: '|' }),
+            ('D', Cell::Door),
+            ('S', Cell::Spawn { facing: None }),
+            ('>', Cell::Spawn { facing: Some(0.0) }),
+            ('v', Cell::Spawn { facing: Some(PI / 2.0) }),
+            ('<', Cell::Spawn { facing: Some(PI) }),
+            ('^', Cell::Spawn { facing: Some(-PI / 2.0) }),
+            ('E', Cell::Exit),
+            ('A', Cell::Pickup { kind: 'A' }),
+            ('B', Cell::Pickup { kind: 'B' }),
+            ('G', Cell::Pickup { kind: 'G' }),
+            ('X', Cell::Enemy { kind: 'X' }),
+            ('L', Cell::Hazard { kind: HazardKind::Lava }),
+            ('T', Cell::Hazard { kind: HazardKind::Spikes }),
+        ]);
+        let mut names: HashMap<char, String> = "S><v^"
+            .chars()
+            .map(|symbol| (symbol, DEFAULT_SPAWN.to_string()))
+            .collect();
+
+        // Spawns con nombre para entrar desde la salida de otro nivel
+        for digit in '1'..='9' {
+            symbols.insert(digit, Cell::Spawn { facing: None });
+            names.insert(digit, digit.to_string());
+        }
+
+        Legend { symbols, names }
+    }
+
+    pub fn get(&self, symbol: char) -> Option<Cell> {
+        self.symbols.get(&symbol).copied()
+    }
+
+    pub fn insert(&mut self, symbol: char, cell: Cell, name: Option<String>) {
+        self.symbols.insert(symbol, cell);
+        match name {
+            Some(name) => self.names.insert(symbol, name),
+            None => self.names.remove(&symbol),
+        };
+    }
+
+    pub fn name(&self, symbol: char) -> Option<&str> {
+        self.names.get(&symbol).map(String::as_str)
+    }
+}
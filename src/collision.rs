@@ -0,0 +1,197 @@
+// collision.rs
+//
+// Colisión de círculos contra la grilla del laberinto. Cada celda sólida (y
+// todo lo que queda fuera del mapa) es un cuadrado de `block_size` de lado;
+// un círculo que se mete en uno se empuja hacia afuera desde el punto más
+// cercano del cuadrado. Así contra una pared recta se desliza y en las
+// esquinas se redondea en vez de trabarse. La usan el jugador, los enemigos
+// y los proyectiles.
+
+use raylib::prelude::*;
+
+use crate::maze::Maze;
+
+// Vueltas de empuje por paso; en una esquina interior hacen falta dos
+const RESOLVE_ITERATIONS: usize = 3;
+
+pub struct Collision {
+    pub pos: Vector2,
+    // Hacia dónde empujaron las paredes, normalizado; None si no hubo choque
+    pub normal: Option<Vector2>,
+}
+
+// Mueve el círculo de `pos` por `delta` y lo saca de las paredes. Los
+// movimientos largos se parten en pasos de medio radio para no atravesar
+// paredes finas.
+pub fn move_circle(maze: &Maze, pos: Vector2, delta: Vector2, radius: f32, block_size: usize) -> Collision {
+    let max_step = (radius * 0.5).max(1.0);
+    let steps = (delta.length() / max_step).ceil().max(1.0) as usize;
+    let mut pos = pos;
+    let mut push = Vector2::zero();
+    for _ in 0..steps {
+        pos += delta / steps as f32;
+        let resolved = push_out(maze, pos, radius, block_size);
+        push += resolved - pos;
+        pos = resolved;
+    }
+    let normal = (push.length() > 0.0).then(|| push / push.length());
+    Collision { pos, normal }
+}
+
+// ¿Toca el círculo alguna pared?
+pub fn overlaps(maze: &Maze, pos: Vector2, radius: f32, block_size: usize) -> bool {
+    solid_cells_near(maze, pos, radius, block_size).any(|(min, max)| penetration(pos, radius, min, max).is_some())
+}
+
+// Quita de la velocidad la parte que va contra la pared; lo que queda es
+// el deslizamiento a lo largo de ella
+pub fn slide(velocity: Vector2, normal: Vector2) -> Vector2 {
+    let into = velocity.dot(normal);
+    if into < 0.0 { velocity - normal * into } else { velocity }
+}
+
+fn push_out(maze: &Maze, pos: Vector2, radius: f32, block_size: usize) -> Vector2 {
+    let mut pos = pos;
+    for _ in 0..RESOLVE_ITERATIONS {
+        // Se resuelve primero la pared más metida; las demás en la vuelta siguiente
+        let deepest = solid_cells_near(maze, pos, radius, block_size)
+            .filter_map(|(min, max)| penetration(pos, radius, min, max))
+            .max_by(|a, b| a.length().total_cmp(&b.length()));
+        match deepest {
+            Some(correction) => pos += correction,
+            None => break,
+        }
+    }
+    pos
+}
+
+// Esquinas (mínima y máxima) de las celdas sólidas que pueden tocar el círculo
+fn solid_cells_near(maze: &Maze, pos: Vector2, radius: f32, block_size: usize) -> impl Iterator<Item = (Vector2, Vector2)> + '_ {
+    let bs = block_size as f32;
+    let first_col = ((pos.x - radius) / bs).floor() as i64;
+    let last_col = ((pos.x + radius) / bs).floor() as i64;
+    let first_row = ((pos.y - radius) / bs).floor() as i64;
+    let last_row = ((pos.y + radius) / bs).floor() as i64;
+
+    (first_row..=last_row)
+        .flat_map(move |row| (first_col..=last_col).map(move |col| (col, row)))
+        .filter(move |&(col, row)| {
+            // Fuera del mapa se considera pared, como en `is_wall`
+            if col < 0 || row < 0 {
+                return true;
+            }
+            maze.get(row as usize).and_then(|cells| cells.get(col as usize)).is_none_or(|cell| cell.is_solid())
+        })
+        .map(move |(col, row)| {
+            let min = Vector2::new(col as f32 * bs, row as f32 * bs);
+            (min, min + Vector2::new(bs, bs))
+        })
+}
+
+// Cuánto hay que mover el círculo para sacarlo del cuadrado [min, max], o
+// None si no se tocan
+fn penetration(pos: Vector2, radius: f32, min: Vector2, max: Vector2) -> Option<Vector2> {
+    let closest = Vector2::new(pos.x.clamp(min.x, max.x), pos.y.clamp(min.y, max.y));
+    let offset = pos - closest;
+    let distance = offset.length();
+
+    if distance > 0.0 {
+        return (distance < radius).then(|| offset * ((radius - distance) / distance));
+    }
+
+    // El centro quedó adentro del cuadrado: sale por el lado más cercano
+    let exits = [
+        Vector2::new(min.x - radius - pos.x, 0.0),
+        Vector2::new(max.x + radius - pos.x, 0.0),
+        Vector2::new(0.0, min.y - radius - pos.y),
+        Vector2::new(0.0, max.y + radius - pos.y),
+    ];
+    exits.into_iter().min_by(|a, b| a.length().total_cmp(&b.length()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::grid;
+
+    const BS: usize = 100;
+    const RADIUS: f32 = 20.0;
+
+    fn room() -> Maze {
+        grid(&["#####", "#   #", "#   #", "#   #", "#####"])
+    }
+
+    fn assert_near(actual: Vector2, expected: Vector2) {
+        assert!((actual - expected).length() < 0.01, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn slides_along_a_straight_wall() {
+        let collision = move_circle(&room(), Vector2::new(250.0, 130.0), Vector2::new(50.0, -40.0), RADIUS, BS);
+        assert_near(collision.pos, Vector2::new(300.0, 120.0));
+        assert_near(collision.normal.unwrap(), Vector2::new(0.0, 1.0));
+        assert_near(slide(Vector2::new(50.0, -40.0), Vector2::new(0.0, 1.0)), Vector2::new(50.0, 0.0));
+        // Alejarse de la pared no se recorta
+        assert_near(slide(Vector2::new(50.0, 40.0), Vector2::new(0.0, 1.0)), Vector2::new(50.0, 40.0));
+    }
+
+    #[test]
+    fn settles_in_an_inside_corner() {
+        let maze = room();
+        // Metido en las dos paredes a la vez: cada vuelta resuelve una
+        let pos = Vector2::new(105.0, 110.0);
+        assert_near(push_out(&maze, pos, RADIUS, BS), Vector2::new(120.0, 120.0));
+
+        let collision = move_circle(&maze, Vector2::new(150.0, 150.0), Vector2::new(-60.0, -60.0), RADIUS, BS);
+        assert_near(collision.pos, Vector2::new(120.0, 120.0));
+        let normal = collision.normal.unwrap();
+        assert!(normal.x > 0.0 && normal.y > 0.0, "{:?}", normal);
+    }
+
+    #[test]
+    fn rounds_outside_corners() {
+        let maze = grid(&["     ", " #   ", "     "]);
+        let pos = push_out(&maze, Vector2::new(205.0, 95.0), RADIUS, BS);
+        assert!((pos - Vector2::new(200.0, 100.0)).length() >= RADIUS - 0.01, "{:?}", pos);
+        assert!(!overlaps(&maze, pos, RADIUS - 0.01, BS));
+    }
+
+    #[test]
+    fn pushes_out_a_center_inside_a_block() {
+        let maze = room();
+        assert_near(
+            penetration(Vector2::new(250.0, 90.0), RADIUS, Vector2::new(200.0, 0.0), Vector2::new(300.0, 100.0)).unwrap(),
+            Vector2::new(0.0, 30.0),
+        );
+        assert_near(push_out(&maze, Vector2::new(250.0, 90.0), RADIUS, BS), Vector2::new(250.0, 120.0));
+        assert!(penetration(Vector2::new(250.0, 130.0), RADIUS, Vector2::new(200.0, 0.0), Vector2::new(300.0, 100.0)).is_none());
+    }
+
+    #[test]
+    fn treats_out_of_map_as_wall() {
+        let open = grid(&["   ", "   ", "   "]);
+        let left = move_circle(&open, Vector2::new(30.0, 150.0), Vector2::new(-50.0, 0.0), RADIUS, BS);
+        assert_near(left.pos, Vector2::new(20.0, 150.0));
+        let right = move_circle(&open, Vector2::new(270.0, 150.0), Vector2::new(50.0, 0.0), RADIUS, BS);
+        assert_near(right.pos, Vector2::new(280.0, 150.0));
+        let down = move_circle(&open, Vector2::new(150.0, 270.0), Vector2::new(0.0, 50.0), RADIUS, BS);
+        assert_near(down.pos, Vector2::new(150.0, 280.0));
+        assert!(overlaps(&open, Vector2::new(150.0, 10.0), RADIUS, BS));
+        assert!(!overlaps(&open, Vector2::new(150.0, 150.0), RADIUS, BS));
+    }
+
+    #[test]
+    fn large_moves_do_not_tunnel() {
+        let maze = grid(&["     ", "  #  ", "     "]);
+        let collision = move_circle(&maze, Vector2::new(50.0, 150.0), Vector2::new(400.0, 0.0), RADIUS, BS);
+        assert_near(collision.pos, Vector2::new(180.0, 150.0));
+        assert_near(collision.normal.unwrap(), Vector2::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn free_moves_have_no_normal() {
+        let collision = move_circle(&room(), Vector2::new(150.0, 150.0), Vector2::new(100.0, 100.0), RADIUS, BS);
+        assert_near(collision.pos, Vector2::new(250.0, 250.0));
+        assert!(collision.normal.is_none());
+    }
+}
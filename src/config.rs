@@ -0,0 +1,243 @@
+// config.rs
+//
+// Opciones del jugador: sensibilidad, ejes invertidos, campo de visión,
+// resolución, pantalla completa, volumen, calidad de render, efectos de
+// cámara, minimapa, controles
+// (una línea `bind.<acción> = tecla, tecla...` por acción) y el ajuste fino
+// del movimiento, que solo se cambia a mano en el archivo. Se leen al
+// arrancar de un archivo `clave = valor` en el directorio de configuración
+// del usuario y se guardan al salir de la pantalla de opciones. Las claves
+// que faltan o no se entienden se quedan con el valor por defecto.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::input::{Action, Binding, Bindings};
+use crate::minimap::MinimapShape;
+use crate::player::Movement;
+use crate::storage::{user_file, write_user_file, UserDir};
+use crate::weapon::OVERLAY_SIZE;
+
+// Tamaño mínimo de la escena dibujada: el arma tiene que entrar entera
+const MIN_RENDER_WIDTH: u32 = 320;
+const MIN_RENDER_HEIGHT: u32 = OVERLAY_SIZE;
+
+// Resoluciones que se pueden elegir en las opciones
+pub const RESOLUTIONS: [(i32, i32); 4] = [(1024, 720), (1300, 900), (1600, 1000), (1920, 1080)];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderQuality {
+    Low,
+    Medium,
+    High,
+}
+
+impl RenderQuality {
+    pub const ALL: [RenderQuality; 3] = [RenderQuality::Low, RenderQuality::Medium, RenderQuality::High];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderQuality::Low => "baja",
+            RenderQuality::Medium => "media",
+            RenderQuality::High => "alta",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RenderQuality> {
+        RenderQuality::ALL.into_iter().find(|quality| quality.name() == name)
+    }
+
+    // Fracción de la resolución de la ventana a la que se dibuja la escena
+    pub fn scale(self) -> f32 {
+        match self {
+            RenderQuality::Low => 0.5,
+            RenderQuality::Medium => 0.75,
+            RenderQuality::High => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub mouse_sensitivity: f32,   // radianes por píxel
+    pub gamepad_sensitivity: f32, // radianes por segundo con el stick al máximo
+    pub deadzone: f32,            // para evitar drift del joystick
+    pub invert_x: bool,
+    pub invert_y: bool,
+    pub fov: f32, // grados
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    pub music_volume: f32, // 0.0 a 1.0
+    pub effects_volume: f32,
+    pub quality: RenderQuality,
+    pub camera_effects: bool, // balanceo al caminar y sacudidas
+    pub minimap_rotate: bool, // el minimapa gira con el jugador
+    pub minimap_shape: MinimapShape,
+    pub bindings: Bindings,
+    pub movement: Movement,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            mouse_sensitivity: 0.005,
+            gamepad_sensitivity: 3.0,
+            deadzone: 0.2,
+            invert_x: false,
+            invert_y: false,
+            fov: 60.0,
+            width: 1300,
+            height: 900,
+            fullscreen: false,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            quality: RenderQuality::High,
+            camera_effects: true,
+            minimap_rotate: false,
+            minimap_shape: MinimapShape::Circle,
+            bindings: Bindings::default(),
+            movement: Movement::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Sin archivo (o sin directorio de configuración) se usan los valores por defecto
+    pub fn load() -> Settings {
+        let mut settings = Settings::default();
+        let Some(path) = config_path() else { return settings };
+        let Ok(text) = fs::read_to_string(&path) else { return settings };
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let applied = line
+                .split_once('=')
+                .is_some_and(|(key, value)| settings.apply(key.trim(), value.trim()));
+            if !applied {
+                eprintln!("{}:{}: opción ignorada: {}", path.display(), index + 1, line);
+            }
+        }
+        settings
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> bool {
+        let number = || value.parse::<f32>().ok().filter(|n| n.is_finite());
+        let flag = || match value {
+            "true" | "si" | "sí" => Some(true),
+            "false" | "no" => Some(false),
+            _ => None,
+        };
+        let set = |target: &mut f32, parsed: Option<f32>, min: f32, max: f32| {
+            parsed.map(|n| *target = n.clamp(min, max)).is_some()
+        };
+
+        match key {
+            "mouse_sensitivity" => set(&mut self.mouse_sensitivity, number(), 0.0005, 0.05),
+            "gamepad_sensitivity" => set(&mut self.gamepad_sensitivity, number(), 0.3, 12.0),
+            "deadzone" => set(&mut self.deadzone, number(), 0.0, 0.9),
+            "invert_x" => flag().map(|on| self.invert_x = on).is_some(),
+            "invert_y" => flag().map(|on| self.invert_y = on).is_some(),
+            "fov" => set(&mut self.fov, number(), 40.0, 120.0),
+            "resolution" => match value.split_once('x').map(|(w, h)| (w.trim().parse(), h.trim().parse())) {
+                Some((Ok(width), Ok(height))) if width >= 320 && height >= 240 => {
+                    self.width = width;
+                    self.height = height;
+                    true
+                }
+                _ => false,
+            },
+            "fullscreen" => flag().map(|on| self.fullscreen = on).is_some(),
+            "music_volume" => set(&mut self.music_volume, number(), 0.0, 1.0),
+            "effects_volume" => set(&mut self.effects_volume, number(), 0.0, 1.0),
+            "quality" => RenderQuality::from_name(value).map(|quality| self.quality = quality).is_some(),
+            "camera_effects" => flag().map(|on| self.camera_effects = on).is_some(),
+            "minimap_rotate" => flag().map(|on| self.minimap_rotate = on).is_some(),
+            "minimap_shape" => MinimapShape::from_name(value).map(|shape| self.minimap_shape = shape).is_some(),
+            "max_speed" => set(&mut self.movement.max_speed, number(), 50.0, 2000.0),
+            "acceleration" => set(&mut self.movement.acceleration, number(), 100.0, 20000.0),
+            "friction" => set(&mut self.movement.friction, number(), 100.0, 20000.0),
+            "run_multiplier" => set(&mut self.movement.run_multiplier, number(), 1.0, 4.0),
+            // Una lista vacía deja la acción sin asignar
+            _ => {
+                let Some(action) = key.strip_prefix("bind.").and_then(Action::from_name) else { return false };
+                let bindings: Option<Vec<Binding>> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(Binding::from_name)
+                    .collect();
+                bindings.map(|bindings| self.bindings.set(action, bindings)).is_some()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = config_path() else { return };
+        let mut text = format!(
+            "# Opciones del raycaster; se reescribe al salir de la pantalla de opciones\n\
+             mouse_sensitivity = {}\n\
+             gamepad_sensitivity = {}\n\
+             deadzone = {}\n\
+             invert_x = {}\n\
+             invert_y = {}\n\
+             fov = {}\n\
+             resolution = {}x{}\n\
+             fullscreen = {}\n\
+             music_volume = {}\n\
+             effects_volume = {}\n\
+             quality = {}\n\
+             camera_effects = {}\n\
+             minimap_rotate = {}\n\
+             minimap_shape = {}\n\
+             max_speed = {}\n\
+             acceleration = {}\n\
+             friction = {}\n\
+             run_multiplier = {}\n",
+            self.mouse_sensitivity,
+            self.gamepad_sensitivity,
+            self.deadzone,
+            self.invert_x,
+            self.invert_y,
+            self.fov,
+            self.width,
+            self.height,
+            self.fullscreen,
+            self.music_volume,
+            self.effects_volume,
+            self.quality.name(),
+            self.camera_effects,
+            self.minimap_rotate,
+            self.minimap_shape.name(),
+            self.movement.max_speed,
+            self.movement.acceleration,
+            self.movement.friction,
+            self.movement.run_multiplier,
+        );
+        for action in Action::ALL {
+            text.push_str(&format!("bind.{} = {}\n", action.name(), self.bindings.names(action)));
+        }
+
+        write_user_file(&path, &text, "las opciones");
+    }
+
+    pub fn fov_radians(&self) -> f32 {
+        self.fov.to_radians()
+    }
+
+    // Tamaño del framebuffer de la escena según la calidad elegida
+    pub fn render_size(&self) -> (u32, u32) {
+        let scale = self.quality.scale();
+        (
+            ((self.width as f32 * scale) as u32).max(MIN_RENDER_WIDTH),
+            ((self.height as f32 * scale) as u32).max(MIN_RENDER_HEIGHT),
+        )
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    user_file(UserDir::Config, "config.txt")
+}
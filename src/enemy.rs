@@ -0,0 +1,228 @@
+// enemy.rs
+//
+// Enemigos con una máquina de estados simple: patrullan sus puntos de ruta,
+// persiguen al jugador cuando lo ven (con un rayo como el de `cast_ray`) y
+// vuelven a la patrulla si lo pierden de vista por un rato. Se mueven con
+// la misma colisión de círculos que el jugador (`move_circle`), así que
+// se deslizan por las paredes, y le quitan vida al tocarlo.
+
+use raylib::prelude::*;
+
+use crate::collision::move_circle;
+use crate::maze::{is_wall, Maze};
+use crate::pathfinding::{cell_center, line_of_sight, smooth_path, world_to_cell, PathOptions, Pathfinder};
+use crate::player::Player;
+use crate::sprites::Sprite;
+
+const PATROL_SPEED: f32 = 120.0; // unidades del mundo por segundo
+const CHASE_SPEED: f32 = 220.0;
+const VIEW_DISTANCE: f32 = 600.0;
+const VIEW_ANGLE: f32 = 2.1; // ~120°
+// A esta distancia se nota al jugador aunque esté a la espalda
+const HEARING_DISTANCE: f32 = 150.0;
+const LOSE_TIME: f32 = 3.0; // segundos sin ver al jugador antes de volver
+const REPATH_TIME: f32 = 0.5;
+const ARRIVE_DISTANCE: f32 = 8.0;
+
+pub const CONTACT_RANGE: f32 = 50.0;
+pub const CONTACT_DAMAGE: f32 = 25.0; // vida por segundo de contacto
+pub const ENEMY_HEALTH: f32 = 60.0;
+// Radio con el que lo alcanzan los disparos
+pub const HIT_RADIUS: f32 = 30.0;
+// Radio para chocar con las paredes, menor que el del jugador para que
+// pase por donde lo persigue
+const ENEMY_RADIUS: f32 = 15.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnemyState {
+    Patrol,
+    Chase,
+    Return,
+}
+
+pub struct Enemy {
+    pub sprite: Sprite,
+    pub state: EnemyState,
+    pub health: f32,
+    facing: f32,
+    patrol: Vec<Vector2>,
+    patrol_index: usize,
+    last_seen: Vector2,
+    lost_time: f32,
+    path: Vec<Vector2>,
+    path_target: Vector2,
+    repath_time: f32,
+}
+
+impl Enemy {
+    pub fn new(sprite: Sprite, patrol: Vec<Vector2>) -> Self {
+        let patrol = if patrol.is_empty() { vec![sprite.pos] } else { patrol };
+        Enemy {
+            state: EnemyState::Patrol,
+            health: ENEMY_HEALTH,
+            facing: 0.0,
+            patrol_index: 0,
+            last_seen: sprite.pos,
+            lost_time: 0.0,
+            path: Vec::new(),
+            path_target: sprite.pos,
+            repath_time: 0.0,
+            sprite,
+            patrol,
+        }
+    }
+
+    pub fn touches(&self, player: &Player) -> bool {
+        distance(self.sprite.pos, player.pos) < CONTACT_RANGE
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    // Un disparo le quita vida y lo pone a perseguir desde donde vino
+    pub fn hit(&mut self, damage: f32, from: Vector2) {
+        self.health -= damage;
+        self.last_seen = from;
+        self.lost_time = 0.0;
+        self.state = EnemyState::Chase;
+    }
+
+    pub fn update(&mut self, dt: f32, maze: &Maze, player: &Player, block_size: usize, pathfinder: &mut Pathfinder) {
+        let sees_player = self.sees(maze, player, block_size);
+        if sees_player {
+            self.last_seen = player.pos;
+            self.lost_time = 0.0;
+            self.state = EnemyState::Chase;
+        }
+
+        match self.state {
+            EnemyState::Patrol => {
+                let target = self.patrol[self.patrol_index];
+                if self.move_towards(target, PATROL_SPEED, dt, maze, block_size, pathfinder) {
+                    self.patrol_index = (self.patrol_index + 1) % self.patrol.len();
+                }
+            }
+            EnemyState::Chase => {
+                if !sees_player {
+                    self.lost_time += dt;
+                    if self.lost_time > LOSE_TIME {
+                        self.state = EnemyState::Return;
+                        self.patrol_index = self.nearest_patrol_point();
+                        return;
+                    }
+                }
+                self.move_towards(self.last_seen, CHASE_SPEED, dt, maze, block_size, pathfinder);
+            }
+            EnemyState::Return => {
+                let target = self.patrol[self.patrol_index];
+                if self.move_towards(target, PATROL_SPEED, dt, maze, block_size, pathfinder) {
+                    self.state = EnemyState::Patrol;
+                }
+            }
+        }
+    }
+
+    // Ve al jugador si está cerca, dentro de su cono de visión y sin paredes en medio
+    fn sees(&self, maze: &Maze, player: &Player, block_size: usize) -> bool {
+        let to_player = player.pos - self.sprite.pos;
+        let dist = distance(player.pos, self.sprite.pos);
+        if dist > VIEW_DISTANCE {
+            return false;
+        }
+        let angle = to_player.y.atan2(to_player.x);
+        let mut diff = angle - self.facing;
+        diff = (diff + std::f32::consts::PI).rem_euclid(2.0 * std::f32::consts::PI) - std::f32::consts::PI;
+        if dist > HEARING_DISTANCE && diff.abs() > VIEW_ANGLE / 2.0 {
+            return false;
+        }
+        can_see(maze, self.sprite.pos, player.pos, block_size)
+    }
+
+    fn nearest_patrol_point(&self) -> usize {
+        (0..self.patrol.len())
+            .min_by(|&a, &b| {
+                distance(self.patrol[a], self.sprite.pos).total_cmp(&distance(self.patrol[b], self.sprite.pos))
+            })
+            .unwrap_or(0)
+    }
+
+    // Avanza hacia `target`, en línea recta si se puede y si no siguiendo un
+    // camino de la grilla. Devuelve true al llegar.
+    fn move_towards(
+        &mut self,
+        target: Vector2,
+        speed: f32,
+        dt: f32,
+        maze: &Maze,
+        block_size: usize,
+        pathfinder: &mut Pathfinder,
+    ) -> bool {
+        if distance(self.sprite.pos, target) < ARRIVE_DISTANCE {
+            return true;
+        }
+
+        // Los enemigos no abren puertas: una cerrada les corta el camino
+        let options = PathOptions::default();
+        let next = if line_of_sight(maze, self.sprite.pos, target, ENEMY_RADIUS, block_size, options) {
+            self.path.clear();
+            target
+        } else {
+            self.repath_time -= dt;
+            if self.path.is_empty() || self.repath_time <= 0.0 || distance(self.path_target, target) > block_size as f32 {
+                self.repath_time = REPATH_TIME;
+                self.path_target = target;
+                let start = world_to_cell(self.sprite.pos, block_size);
+                let goal = world_to_cell(target, block_size);
+                self.path = pathfinder
+                    .find_path(maze, start, goal, options)
+                    .map(|cells| smooth_path(maze, &cells, ENEMY_RADIUS, block_size, options))
+                    .unwrap_or_else(|| vec![cell_center(goal, block_size)]);
+                // El primer punto es el centro de la celda actual
+                if self.path.len() > 1 {
+                    self.path.remove(0);
+                }
+            }
+            while self.path.len() > 1 && distance(self.sprite.pos, self.path[0]) < ARRIVE_DISTANCE {
+                self.path.remove(0);
+            }
+            self.path[0]
+        };
+
+        let to_next = next - self.sprite.pos;
+        let length = distance(next, self.sprite.pos).max(0.0001);
+        let step = (speed * dt).min(length);
+        self.facing = to_next.y.atan2(to_next.x);
+
+        let collision = move_circle(maze, self.sprite.pos, to_next * (step / length), ENEMY_RADIUS, block_size);
+        self.set_pos(collision.pos);
+        false
+    }
+
+    fn set_pos(&mut self, pos: Vector2) {
+        self.sprite.pos = pos;
+        self.sprite.x = pos.x;
+        self.sprite.y = pos.y;
+    }
+}
+
+fn distance(a: Vector2, b: Vector2) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+// Recorre el rayo de `from` a `to` de a una unidad, como `cast_ray`, y
+// corta en la primera pared
+pub fn can_see(maze: &Maze, from: Vector2, to: Vector2, block_size: usize) -> bool {
+    let dist = distance(from, to);
+    let a = (to.y - from.y).atan2(to.x - from.x);
+    let mut d = 0.0;
+    while d < dist {
+        let x = from.x + d * a.cos();
+        let y = from.y + d * a.sin();
+        if is_wall(x, y, maze, block_size) {
+            return false;
+        }
+        d += 1.0;
+    }
+    true
+}
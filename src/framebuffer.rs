@@ -22,6 +22,13 @@ impl Framebuffer {
         }
     }
 
+    // Cambia el tamaño (por ejemplo al cambiar la resolución o la calidad)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.clear();
+    }
+
     pub fn clear(&mut self) {
         self.color_buffer = Image::gen_image_color(self.width as i32, self.height as i32, self.background_color);
     }
@@ -40,32 +47,44 @@ impl Framebuffer {
         self.current_color = color;
     }
 
+    pub fn draw_text(&mut self, text: &str, x: i32, y: i32, font_size: i32, color: Color) {
+        self.color_buffer.draw_text(text, x, y, font_size, color);
+    }
+
     pub fn _render_to_file(&self, file_path: &str) {
         self.color_buffer.export_image(file_path);
     }
 
+   // `overlay` dibuja encima en coordenadas de la ventana, después de
+   // escalar el framebuffer (por ejemplo el menú de pausa)
    pub fn swap_buffers(
     &self,
     window: &mut RaylibHandle,
     raylib_thread: &RaylibThread,
+    overlay: impl FnOnce(&mut RaylibDrawHandle),
 ) {
     if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
         
         let fps = window.get_fps();
+        // Con calidad de render menor el framebuffer es más chico que la ventana
+        let screen_width = window.get_screen_width();
+        let scale = screen_width as f32 / self.width as f32;
 
         let mut renderer = window.begin_drawing(raylib_thread);
 
         
-        renderer.draw_texture(&texture, 0, 0, Color::WHITE);
+        renderer.draw_texture_ex(&texture, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
 
         // Dibujar FPS
         renderer.draw_text(
             &format!("FPS: {}", fps),
-            1200,
+            screen_width - 100,
             10,
             20,
             Color::BLUEVIOLET,
         );
+
+        overlay(&mut renderer);
     }
 }
 }
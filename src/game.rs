@@ -0,0 +1,688 @@
+// game.rs
+//
+// Máquina de estados del juego. Cada pantalla es un `GameState` con sus
+// ganchos `enter`, `exit`, `update` y `render`; `update` devuelve el estado
+// siguiente cuando hay que cambiar y el bucle principal llama a `exit` del
+// actual y a `enter` del nuevo. Así una pantalla nueva es una variante y
+// unos brazos de `match`, no otro bucle anidado en `main`.
+
+use raylib::prelude::*;
+
+use crate::audio::Audio;
+use crate::framebuffer::Framebuffer;
+use crate::generator::{self, GeneratorConfig};
+use crate::maze::{load_maze, Level};
+use crate::play::{LevelOutcome, PlaySession};
+use crate::textures::TextureManager;
+use crate::campaign::{Campaign, Progress};
+use crate::config::{RenderQuality, Settings, RESOLUTIONS};
+use crate::input::{Action, Binding, Bindings};
+use crate::minimap::MinimapShape;
+use crate::{playable, CAMPAIGN_FILE, MENU_MUSIC, VICTORY_MUSIC};
+
+// Vidas con las que se empieza una partida; se pierde una al morir
+const START_LIVES: u32 = 3;
+
+// La simulación avanza en pasos fijos, sin importar a cuántos FPS se dibuje
+const SIM_DT: f32 = 1.0 / 60.0;
+// Después de un tirón (cargar texturas, mover la ventana) no se intenta
+// recuperar más de este tiempo de golpe
+const MAX_FRAME_TIME: f32 = 0.25;
+
+const PAUSE_OPTIONS: [&str; 4] = ["Continuar", "Reiniciar nivel", "Opciones", "Salir al menú"];
+// Entradas de la pantalla de opciones; las dos últimas son "Controles" y "Volver"
+const SETTINGS_OPTIONS: usize = 16;
+// Entradas de la pantalla de controles: una por acción, "Restaurar" y "Volver"
+const CONTROLS_OPTIONS: usize = Action::ALL.len() + 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameState {
+    Menu,
+    LoadingLevel,
+    Playing,
+    Paused,
+    LevelComplete,
+    GameOver,
+    Victory,
+    Settings,
+    Controls,
+}
+
+enum Mode {
+    // Se encadenan los niveles siguiendo el `next` de cada cabecera
+    Campaign { level_file: String },
+    // Un laberinto aleatorio nuevo por etapa; la semilla es de toda la partida
+    Endless { seed: u64, stage: usize },
+}
+
+// Una partida desde que se elige en el menú hasta que se vuelve a él
+struct Run {
+    mode: Mode,
+    entry: Option<String>, // spawn por el que se entra al nivel actual
+    lives: u32,
+    last_name: String, // nombre y tiempo del último nivel terminado
+    last_time: f32,
+}
+
+impl Run {
+    // Nivel actual listo para jugar, o None si no carga o no se puede completar
+    fn load(&self) -> Option<Level> {
+        match &self.mode {
+            Mode::Campaign { level_file } => {
+                let level = match load_maze(level_file) {
+                    Ok(level) => level,
+                    Err(e) => {
+                        eprintln!("Error al cargar {}: {}", level_file, e);
+                        return None;
+                    }
+                };
+                let level = playable(level, level_file);
+                if level.is_none() {
+                    eprintln!("{} no se puede completar; se vuelve al menú", level_file);
+                }
+                level
+            }
+            Mode::Endless { seed, stage } => {
+                match generator::generate_level(&GeneratorConfig::endless(*seed, *stage)) {
+                    // La semilla queda a la vista (HUD y pantalla de nivel
+                    // completado) para repetir la partida con `--seed`
+                    Ok(mut level) => {
+                        level.meta.name = format!("Infinito, etapa {} (semilla {})", stage + 1, seed);
+                        playable(level, "laberinto aleatorio")
+                    }
+                    Err(e) => {
+                        eprintln!("Error al generar el laberinto: {}", e);
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct Game {
+    state: GameState,
+    settings: Settings,
+    audio: Audio,
+    framebuffer: Framebuffer,
+    texture_cache: TextureManager,
+    campaign: Campaign,
+    progress: Progress,
+    start_screen: Texture2D,
+    end_screen: Texture2D,
+    menu_selection: usize,
+    pause_selection: usize,
+    settings_selection: usize,
+    settings_return: GameState, // a dónde vuelven las opciones: menú o pausa
+    controls_selection: usize,
+    rebinding: bool, // esperando la tecla nueva para la acción elegida
+    run: Option<Run>,
+    session: Option<PlaySession>,
+    accumulator: f32, // tiempo real que la simulación todavía no avanzó
+    endless_seed: Option<u64>, // `--seed`: el modo infinito repite esa partida
+    quit: bool,
+}
+
+impl Game {
+    pub fn new(
+        window: &mut RaylibHandle,
+        raylib_thread: &RaylibThread,
+        settings: Settings,
+        audio: Audio,
+        framebuffer: Framebuffer,
+        texture_cache: TextureManager,
+        endless_seed: Option<u64>,
+    ) -> Self {
+        let start_screen = window.load_texture(raylib_thread, "assets/start.png").unwrap();
+        let end_screen = window.load_texture(raylib_thread, "assets/end.png").unwrap();
+        // Sin campaña solo queda el modo infinito
+        let campaign = Campaign::load(CAMPAIGN_FILE).unwrap_or_else(|e| {
+            eprintln!("Error al cargar la campaña: {}", e);
+            Campaign { levels: Vec::new() }
+        });
+        Game {
+            state: GameState::Menu,
+            settings,
+            audio,
+            framebuffer,
+            texture_cache,
+            campaign,
+            progress: Progress::load(),
+            start_screen,
+            end_screen,
+            menu_selection: 0,
+            pause_selection: 0,
+            settings_selection: 0,
+            settings_return: GameState::Menu,
+            controls_selection: 0,
+            rebinding: false,
+            run: None,
+            session: None,
+            accumulator: 0.0,
+            endless_seed,
+            quit: false,
+        }
+    }
+
+    pub fn run(&mut self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) {
+        self.apply_settings(window);
+        self.enter(window, GameState::Menu);
+        while !self.quit && !window.window_should_close() {
+            let dt = window.get_frame_time();
+            self.audio.update_music(dt);
+
+            if let Some(next) = self.update(window, dt) {
+                self.exit(window, next);
+                self.state = next;
+                self.enter(window, next);
+            }
+            if self.quit {
+                break;
+            }
+
+            self.render(window, raylib_thread);
+        }
+        self.audio.stop_all();
+    }
+
+    // Opciones del menú principal: los niveles de la campaña, el modo
+    // infinito y las opciones
+    fn endless_option(&self) -> usize {
+        self.campaign.levels.len()
+    }
+
+    fn settings_option(&self) -> usize {
+        self.campaign.levels.len() + 1
+    }
+
+    fn lives(&self) -> u32 {
+        self.run.as_ref().map_or(0, |run| run.lives)
+    }
+
+    fn enter(&mut self, window: &mut RaylibHandle, state: GameState) {
+        match state {
+            GameState::Menu => {
+                self.run = None;
+                self.audio.play_music(MENU_MUSIC);
+            }
+            // El mouse solo queda capturado mientras se juega; el tiempo que
+            // pasó en pausa o cargando no cuenta para la simulación
+            GameState::Playing => {
+                window.disable_cursor();
+                self.accumulator = 0.0;
+            }
+            GameState::Paused => {
+                self.pause_selection = 0;
+                // Al volver de las opciones el framebuffer puede haber cambiado
+                // de tamaño; el nivel está congelado así que se redibuja igual
+                let lives = self.lives();
+                if let Some(session) = &self.session {
+                    session.render(&mut self.framebuffer, &self.texture_cache, &self.settings, lives);
+                }
+            }
+            GameState::Victory => self.audio.play_music(VICTORY_MUSIC),
+            GameState::Settings => self.settings_selection = 0,
+            GameState::Controls => {
+                self.controls_selection = 0;
+                self.rebinding = false;
+            }
+            GameState::LoadingLevel | GameState::LevelComplete | GameState::GameOver => {}
+        }
+    }
+
+    fn exit(&mut self, window: &mut RaylibHandle, next: GameState) {
+        match self.state {
+            GameState::Playing => window.enable_cursor(),
+            GameState::Settings => self.settings.save(),
+            _ => {}
+        }
+        // El nivel sigue vivo mientras se juega, se está en pausa o en las
+        // opciones (y sus controles) abiertas desde la pausa
+        let keeps_session = match self.state {
+            GameState::Playing | GameState::Paused | GameState::Settings | GameState::Controls => {
+                matches!(next, GameState::Playing | GameState::Paused | GameState::Settings | GameState::Controls)
+            }
+            _ => true,
+        };
+        if !keeps_session && self.session.is_some() {
+            self.session = None;
+            self.audio.stop_all();
+        }
+    }
+
+    fn update(&mut self, window: &mut RaylibHandle, dt: f32) -> Option<GameState> {
+        match self.state {
+            GameState::Menu => self.update_menu(window),
+            GameState::LoadingLevel => {
+                let run = self.run.as_ref()?;
+                let Some(level) = run.load() else { return Some(GameState::Menu) };
+                let entry = run.entry.clone();
+                self.session = Some(PlaySession::new(level, entry.as_deref(), &self.settings, &mut self.texture_cache, &mut self.audio));
+                Some(GameState::Playing)
+            }
+            GameState::Playing => {
+                if self.pause_pressed(window) {
+                    return Some(GameState::Paused);
+                }
+                let session = self.session.as_mut()?;
+                let mut outcome = session.handle_input(window, &self.settings, &mut self.audio);
+                self.accumulator += dt.min(MAX_FRAME_TIME);
+                while outcome.is_none() && self.accumulator >= SIM_DT {
+                    outcome = session.update(window, SIM_DT, &self.settings, &mut self.audio, &self.texture_cache);
+                    self.accumulator -= SIM_DT;
+                }
+                let outcome = outcome?;
+                let (name, time) = (session.name().to_string(), session.elapsed());
+                self.finish_level(outcome, name, time)
+            }
+            GameState::Paused => self.update_pause(window),
+            GameState::LevelComplete => {
+                if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                    return Some(GameState::Menu);
+                }
+                window.is_key_pressed(KeyboardKey::KEY_ENTER).then_some(GameState::LoadingLevel)
+            }
+            GameState::GameOver => {
+                if self.lives() > 0 && window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    return Some(GameState::LoadingLevel);
+                }
+                let back = window.is_key_pressed(KeyboardKey::KEY_ESCAPE)
+                    || (self.lives() == 0 && window.is_key_pressed(KeyboardKey::KEY_ENTER));
+                back.then_some(GameState::Menu)
+            }
+            GameState::Victory => {
+                let done = window.is_key_pressed(KeyboardKey::KEY_ENTER) || window.is_key_pressed(KeyboardKey::KEY_ESCAPE);
+                done.then_some(GameState::Menu)
+            }
+            GameState::Settings => self.update_settings(window),
+            GameState::Controls => self.update_controls(window),
+        }
+    }
+
+    // ESC pausa siempre, además de lo que tenga asignado la acción
+    fn pause_pressed(&self, window: &RaylibHandle) -> bool {
+        window.is_key_pressed(KeyboardKey::KEY_ESCAPE) || self.settings.bindings.pressed(window, Action::Pause)
+    }
+
+    fn update_menu(&mut self, window: &RaylibHandle) -> Option<GameState> {
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) { self.menu_selection = (self.menu_selection + 1).min(self.settings_option()); }
+        if window.is_key_pressed(KeyboardKey::KEY_UP) { self.menu_selection = self.menu_selection.saturating_sub(1); }
+        if self.settings.bindings.pressed(window, Action::Mute) { self.audio.toggle_mute(); }
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.quit = true;
+            return None;
+        }
+        if !window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return None;
+        }
+
+        let mode = match self.menu_selection {
+            index if index == self.settings_option() => {
+                self.settings_return = GameState::Menu;
+                return Some(GameState::Settings);
+            }
+            index if index == self.endless_option() => {
+                let seed = self.endless_seed.unwrap_or_else(|| {
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|elapsed| elapsed.as_nanos() as u64)
+                        .unwrap_or(0)
+                });
+                eprintln!("Modo infinito con semilla {}", seed);
+                Mode::Endless { seed, stage: 0 }
+            }
+            // Los niveles bloqueados no se pueden elegir
+            index if !self.campaign.is_unlocked(index, &self.progress) => return None,
+            index => Mode::Campaign { level_file: self.campaign.levels[index].file.clone() },
+        };
+        self.run = Some(Run { mode, entry: None, lives: START_LIVES, last_name: String::new(), last_time: 0.0 });
+        Some(GameState::LoadingLevel)
+    }
+
+    fn update_pause(&mut self, window: &RaylibHandle) -> Option<GameState> {
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) { self.pause_selection = (self.pause_selection + 1).min(PAUSE_OPTIONS.len() - 1); }
+        if window.is_key_pressed(KeyboardKey::KEY_UP) { self.pause_selection = self.pause_selection.saturating_sub(1); }
+        if self.settings.bindings.pressed(window, Action::Mute) { self.audio.toggle_mute(); }
+        if self.pause_pressed(window) {
+            return Some(GameState::Playing);
+        }
+        if !window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return None;
+        }
+        match self.pause_selection {
+            0 => Some(GameState::Playing),
+            1 => Some(GameState::LoadingLevel),
+            2 => {
+                self.settings_return = GameState::Paused;
+                Some(GameState::Settings)
+            }
+            _ => Some(GameState::Menu),
+        }
+    }
+
+    // ← y → cambian la opción elegida; ENTER también sirve para las que
+    // se alternan. Los cambios se aplican en el momento.
+    fn update_settings(&mut self, window: &mut RaylibHandle) -> Option<GameState> {
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) { self.settings_selection = (self.settings_selection + 1).min(SETTINGS_OPTIONS - 1); }
+        if window.is_key_pressed(KeyboardKey::KEY_UP) { self.settings_selection = self.settings_selection.saturating_sub(1); }
+        if self.settings.bindings.pressed(window, Action::Mute) { self.audio.toggle_mute(); }
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            return Some(self.settings_return);
+        }
+
+        let step = if window.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            -1.0
+        } else if window.is_key_pressed(KeyboardKey::KEY_RIGHT) || window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            1.0
+        } else {
+            return None;
+        };
+        // "Controles" y "Volver" solo responden a ENTER
+        if self.settings_selection == SETTINGS_OPTIONS - 2 {
+            return window.is_key_pressed(KeyboardKey::KEY_ENTER).then_some(GameState::Controls);
+        }
+        if self.settings_selection == SETTINGS_OPTIONS - 1 {
+            return window.is_key_pressed(KeyboardKey::KEY_ENTER).then_some(self.settings_return);
+        }
+
+        let settings = &mut self.settings;
+        let nudge = |value: &mut f32, amount: f32, min: f32, max: f32| *value = (*value + amount * step).clamp(min, max);
+        let cycle = |index: usize, len: usize| (index as isize + step as isize).rem_euclid(len as isize) as usize;
+        match self.settings_selection {
+            0 => nudge(&mut settings.music_volume, 0.1, 0.0, 1.0),
+            1 => nudge(&mut settings.effects_volume, 0.1, 0.0, 1.0),
+            2 => nudge(&mut settings.mouse_sensitivity, 0.0005, 0.0005, 0.05),
+            3 => nudge(&mut settings.gamepad_sensitivity, 0.3, 0.3, 12.0),
+            4 => nudge(&mut settings.deadzone, 0.05, 0.0, 0.9),
+            5 => settings.invert_x = !settings.invert_x,
+            6 => settings.invert_y = !settings.invert_y,
+            7 => nudge(&mut settings.fov, 5.0, 40.0, 120.0),
+            8 => {
+                let current = RESOLUTIONS.iter().position(|&size| size == (settings.width, settings.height));
+                let next = current.map_or(0, |index| cycle(index, RESOLUTIONS.len()));
+                (settings.width, settings.height) = RESOLUTIONS[next];
+            }
+            9 => settings.fullscreen = !settings.fullscreen,
+            11 => settings.camera_effects = !settings.camera_effects,
+            12 => settings.minimap_rotate = !settings.minimap_rotate,
+            13 => {
+                let current = MinimapShape::ALL.iter().position(|&shape| shape == settings.minimap_shape).unwrap_or(0);
+                settings.minimap_shape = MinimapShape::ALL[cycle(current, MinimapShape::ALL.len())];
+            }
+            _ => {
+                let current = RenderQuality::ALL.iter().position(|&quality| quality == settings.quality).unwrap_or(0);
+                settings.quality = RenderQuality::ALL[cycle(current, RenderQuality::ALL.len())];
+            }
+        }
+        self.apply_settings(window);
+        None
+    }
+
+    // ENTER espera la tecla, botón o eje nuevo y lo agrega a la acción;
+    // SUPR o BACKSPACE la dejan sin asignar. Los cambios se guardan al
+    // salir de las opciones.
+    fn update_controls(&mut self, window: &mut RaylibHandle) -> Option<GameState> {
+        let bindings = &mut self.settings.bindings;
+        if self.rebinding {
+            if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                self.rebinding = false;
+            } else if let Some(binding) = Binding::capture(window) {
+                bindings.add(Action::ALL[self.controls_selection], binding);
+                self.rebinding = false;
+            }
+            return None;
+        }
+
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) { self.controls_selection = (self.controls_selection + 1).min(CONTROLS_OPTIONS - 1); }
+        if window.is_key_pressed(KeyboardKey::KEY_UP) { self.controls_selection = self.controls_selection.saturating_sub(1); }
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            return Some(GameState::Settings);
+        }
+
+        let action = Action::ALL.get(self.controls_selection).copied();
+        if let Some(action) = action
+            && (window.is_key_pressed(KeyboardKey::KEY_DELETE) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE))
+        {
+            bindings.clear(action);
+        }
+        if !window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return None;
+        }
+        match action {
+            Some(_) => self.rebinding = true,
+            None if self.controls_selection == CONTROLS_OPTIONS - 2 => *bindings = Bindings::default(),
+            None => return Some(GameState::Settings),
+        }
+        None
+    }
+
+    // Pasa las opciones a la ventana, el framebuffer y el audio
+    fn apply_settings(&mut self, window: &mut RaylibHandle) {
+        let settings = &self.settings;
+        if window.is_window_fullscreen() != settings.fullscreen {
+            window.toggle_fullscreen();
+        }
+        if !settings.fullscreen && (window.get_screen_width(), window.get_screen_height()) != (settings.width, settings.height) {
+            window.set_window_size(settings.width, settings.height);
+        }
+        let (width, height) = settings.render_size();
+        if (self.framebuffer.width, self.framebuffer.height) != (width, height) {
+            self.framebuffer.resize(width, height);
+        }
+        self.audio.set_volumes(settings.music_volume, settings.effects_volume);
+    }
+
+    fn settings_labels(&self) -> [String; SETTINGS_OPTIONS] {
+        let settings = &self.settings;
+        let defaults = Settings::default();
+        let yes_no = |on: bool| if on { "sí" } else { "no" };
+        [
+            format!("Volumen de música: {:.0}%", settings.music_volume * 100.0),
+            format!("Volumen de efectos: {:.0}%", settings.effects_volume * 100.0),
+            format!("Sensibilidad del mouse: x{:.1}", settings.mouse_sensitivity / defaults.mouse_sensitivity),
+            format!("Sensibilidad del control: x{:.1}", settings.gamepad_sensitivity / defaults.gamepad_sensitivity),
+            format!("Zona muerta del control: {:.2}", settings.deadzone),
+            format!("Invertir eje X: {}", yes_no(settings.invert_x)),
+            format!("Invertir eje Y: {}", yes_no(settings.invert_y)),
+            format!("Campo de visión: {:.0}°", settings.fov),
+            format!("Resolución: {}x{}", settings.width, settings.height),
+            format!("Pantalla completa: {}", yes_no(settings.fullscreen)),
+            format!("Calidad de render: {}", settings.quality.name()),
+            format!("Movimiento de cámara: {}", yes_no(settings.camera_effects)),
+            format!("Minimapa girando con el jugador: {}", yes_no(settings.minimap_rotate)),
+            format!("Forma del minimapa: {}", settings.minimap_shape.label()),
+            "Controles".to_string(),
+            "Volver".to_string(),
+        ]
+    }
+
+    // Decide a qué pantalla se va cuando termina un nivel
+    fn finish_level(&mut self, outcome: LevelOutcome, name: String, time: f32) -> Option<GameState> {
+        let run = self.run.as_mut()?;
+        match outcome {
+            LevelOutcome::Won { next_level, next_entry } => {
+                run.last_name = name;
+                run.last_time = time;
+                match &mut run.mode {
+                    Mode::Campaign { level_file } => {
+                        self.progress.complete(level_file, time);
+                        self.progress.save();
+                        match self.campaign.next_level(level_file, next_level.as_deref()) {
+                            Some(next) => {
+                                *level_file = next;
+                                run.entry = next_entry;
+                                Some(GameState::LevelComplete)
+                            }
+                            None => Some(GameState::Victory),
+                        }
+                    }
+                    Mode::Endless { stage, .. } => {
+                        *stage += 1;
+                        Some(GameState::LevelComplete)
+                    }
+                }
+            }
+            // Se vuelve a cargar el mismo nivel entrando por el mismo spawn;
+            // en el modo infinito la semilla no cambia y se repite la etapa
+            LevelOutcome::Restart => Some(GameState::LoadingLevel),
+            LevelOutcome::Died => {
+                run.lives = run.lives.saturating_sub(1);
+                Some(GameState::GameOver)
+            }
+            LevelOutcome::Quit => {
+                self.quit = true;
+                None
+            }
+        }
+    }
+
+    fn render(&mut self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) {
+        let lives = self.lives();
+        match self.state {
+            GameState::Playing => {
+                if let Some(session) = &self.session {
+                    session.render(&mut self.framebuffer, &self.texture_cache, &self.settings, lives);
+                }
+                self.framebuffer.swap_buffers(window, raylib_thread, |_| {});
+            }
+            // La simulación está congelada: se reusa el último frame del
+            // nivel y el menú de pausa se dibuja encima en la ventana, para
+            // que no cambie de tamaño con la calidad de render
+            GameState::Paused => {
+                let selection = self.pause_selection;
+                self.framebuffer.swap_buffers(window, raylib_thread, |d| {
+                    let (width, height) = (d.get_screen_width(), d.get_screen_height());
+                    let (panel_w, panel_h) = (420, 120 + 50 * PAUSE_OPTIONS.len() as i32);
+                    let (left, top) = ((width - panel_w) / 2, (height - panel_h) / 2);
+                    d.draw_rectangle(left, top, panel_w, panel_h, Color::new(20, 20, 40, 255));
+                    centered(d, "Pausa", top + 25 - height / 2, 48, Color::WHITE);
+                    for (index, option) in PAUSE_OPTIONS.iter().enumerate() {
+                        let color = if index == selection { Color::YELLOW } else { Color::LIGHTGRAY };
+                        d.draw_text(option, left + 60, top + 100 + 50 * index as i32, 32, color);
+                    }
+                });
+            }
+            _ => {
+                let mut d = window.begin_drawing(raylib_thread);
+                d.clear_background(Color::BLACK);
+                self.render_screen(&mut d);
+            }
+        }
+    }
+
+    // Pantallas que no usan el framebuffer del raycaster
+    fn render_screen(&self, d: &mut RaylibDrawHandle) {
+        let (width, height) = (d.get_screen_width(), d.get_screen_height());
+        let full_screen = |d: &mut RaylibDrawHandle, texture: &Texture2D| {
+            d.draw_texture_pro(
+                texture,
+                Rectangle { x:0.0, y:0.0, width:texture.width() as f32, height:texture.height() as f32 },
+                Rectangle { x:0.0, y:0.0, width:width as f32, height:height as f32 },
+                Vector2::new(0.0,0.0),
+                0.0,
+                Color::WHITE
+            );
+        };
+
+        match self.state {
+            GameState::Menu => {
+                full_screen(d, &self.start_screen);
+                // Los niveles muestran su mejor tiempo o si siguen bloqueados
+                let mut options: Vec<(String, bool)> = self.campaign.levels.iter().enumerate()
+                    .map(|(index, level)| {
+                        let unlocked = self.campaign.is_unlocked(index, &self.progress);
+                        let label = match self.progress.best_time(&level.file) {
+                            _ if !unlocked => format!("{} (bloqueado)", level.name),
+                            Some(best) => format!("{} - mejor: {:.0} s", level.name, best),
+                            None => level.name.clone(),
+                        };
+                        (label, unlocked)
+                    })
+                    .collect();
+                options.push(("Modo infinito".to_string(), true));
+                options.push(("Opciones".to_string(), true));
+
+                // La lista queda pegada al borde de abajo sin tapar el título
+                let top = (height / 2 - 20 - 50 * options.len() as i32).max(60 - height / 2);
+                centered(d, "Selecciona un nivel con ↑ y ↓, ENTER para empezar", top - 50, 26, Color::WHITE);
+                for (index, (label, enabled)) in options.iter().enumerate() {
+                    let color = match (index == self.menu_selection, enabled) {
+                        (true, _) => Color::YELLOW,
+                        (false, true) => Color::LIGHTGRAY,
+                        (false, false) => Color::DARKGRAY,
+                    };
+                    centered(d, label, top + 50 * index as i32, 36, color);
+                }
+            }
+            GameState::LoadingLevel => {
+                centered(d, "Cargando...", -30, 36, Color::WHITE);
+            }
+            GameState::LevelComplete => {
+                let Some(run) = &self.run else { return };
+                centered(d, "¡Nivel completado!", -90, 48, Color::YELLOW);
+                centered(d, &format!("{}   Tiempo: {:.0} s", run.last_name, run.last_time), -10, 28, Color::WHITE);
+                centered(d, "ENTER para continuar", 50, 26, Color::WHITE);
+            }
+            // Sin vidas solo se puede volver al menú
+            GameState::GameOver => {
+                if self.lives() == 0 {
+                    centered(d, "Fin del juego", -70, 48, Color::RED);
+                    centered(d, "ENTER o ESC para volver al menú", 10, 26, Color::WHITE);
+                } else {
+                    centered(d, "Has muerto", -70, 48, Color::RED);
+                    centered(d, &format!("Vidas restantes: {}", self.lives()), 0, 28, Color::WHITE);
+                    centered(d, "ENTER para reintentar, ESC para volver al menú", 60, 26, Color::WHITE);
+                }
+            }
+            GameState::Victory => {
+                full_screen(d, &self.end_screen);
+                centered(d, "¡Felicidades! Has completado la campaña.", height / 2 - 100, 28, Color::WHITE);
+            }
+            GameState::Settings => {
+                let top = ((height - 34 * SETTINGS_OPTIONS as i32) / 2).max(70);
+                d.draw_text("Opciones", width / 2 - 100, top - 60, 40, Color::WHITE);
+                for (index, label) in self.settings_labels().iter().enumerate() {
+                    let color = if index == self.settings_selection { Color::YELLOW } else { Color::LIGHTGRAY };
+                    d.draw_text(label, width / 2 - 220, top + 34 * index as i32, 26, color);
+                }
+                d.draw_text("↑ ↓ para elegir, ← → para cambiar, ESC para volver", width / 2 - 300, height - 50, 22, Color::GRAY);
+            }
+            GameState::Controls => {
+                let top = ((height - 28 * CONTROLS_OPTIONS as i32) / 2).max(60);
+                d.draw_text("Controles", width / 2 - 100, top - 55, 40, Color::WHITE);
+                let bindings = &self.settings.bindings;
+                for (index, action) in Action::ALL.iter().enumerate() {
+                    let selected = index == self.controls_selection;
+                    let assigned = match bindings.names(*action) {
+                        _ if selected && self.rebinding => "...".to_string(),
+                        names if names.is_empty() => "(sin asignar)".to_string(),
+                        names => names,
+                    };
+                    let color = if selected { Color::YELLOW } else { Color::LIGHTGRAY };
+                    d.draw_text(action.label(), width / 2 - 420, top + 28 * index as i32, 22, color);
+                    d.draw_text(&assigned, width / 2 + 20, top + 28 * index as i32, 22, color);
+                }
+                for (offset, label) in ["Restaurar valores por defecto", "Volver"].iter().enumerate() {
+                    let index = Action::ALL.len() + offset;
+                    let color = if index == self.controls_selection { Color::YELLOW } else { Color::LIGHTGRAY };
+                    d.draw_text(label, width / 2 - 420, top + 28 * index as i32, 22, color);
+                }
+                let help = if self.rebinding {
+                    "Aprieta una tecla, botón o eje para agregarlo; ESC para cancelar"
+                } else {
+                    "ENTER para agregar una tecla, SUPR para borrar las de la acción, ESC para volver"
+                };
+                d.draw_text(help, width / 2 - 420, height - 30, 22, Color::GRAY);
+            }
+            GameState::Playing | GameState::Paused => {}
+        }
+    }
+}
+
+// Texto centrado en horizontal; `y` se cuenta desde el centro de la ventana
+// para que las pantallas sirvan en cualquier resolución
+fn centered(d: &mut RaylibDrawHandle, text: &str, y: i32, size: i32, color: Color) {
+    let (width, height) = (d.get_screen_width(), d.get_screen_height());
+    let x = (width - d.measure_text(text, size)) / 2;
+    d.draw_text(text, x, height / 2 + y, size, color);
+}
@@ -0,0 +1,471 @@
+// generator.rs
+//
+// Generador de laberintos perfectos en el mismo formato ASCII de maze.txt:
+// cada celda ocupa dos caracteres de ancho entre esquinas `+`, con `--`
+// y `|` como paredes. El resultado es un archivo de nivel completo
+// (cabecera + `[grid]`) que se carga con `maze::parse_level`.
+
+use crate::maze::{parse_level, Level, LevelError};
+
+const PICKUP_KINDS: [char; 3] = ['A', 'B', 'G'];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+    RecursiveBacktracker,
+    Kruskal,
+    Prim,
+    Eller,
+    Wilson,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::RecursiveBacktracker,
+        Algorithm::Kruskal,
+        Algorithm::Prim,
+        Algorithm::Eller,
+        Algorithm::Wilson,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::RecursiveBacktracker => "backtracker",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Prim => "prim",
+            Algorithm::Eller => "eller",
+            Algorithm::Wilson => "wilson",
+        }
+    }
+}
+
+pub struct GeneratorConfig {
+    pub algorithm: Algorithm,
+    pub seed: u64,
+    pub width: usize,  // en celdas, no en caracteres
+    pub height: usize,
+    pub braid: f32,    // fracción de callejones sin salida que se abren (0 = laberinto perfecto)
+    pub pickups: usize,
+}
+
+impl GeneratorConfig {
+    // Configuración de la etapa `stage` del modo infinito: los laberintos
+    // crecen y rotan de algoritmo, siempre a partir de la misma semilla
+    pub fn endless(seed: u64, stage: usize) -> Self {
+        GeneratorConfig {
+            algorithm: Algorithm::ALL[stage % Algorithm::ALL.len()],
+            seed: seed.wrapping_add(stage as u64),
+            width: (4 + stage).min(16),
+            height: (4 + stage / 2).min(12),
+            braid: 0.2,
+            pickups: (3 + stage / 2).min(10),
+        }
+    }
+}
+
+// Generador pseudoaleatorio SplitMix64: misma semilla, mismo laberinto
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Entero en [0, n)
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        ((self.next_u64() >> 40) as f32 / (1u64 << 24) as f32) < probability
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+// Celdas en orden fila por fila; `east[c]`/`south[c]` indican si hay paso
+// hacia la celda de la derecha/de abajo
+struct Grid {
+    width: usize,
+    height: usize,
+    east: Vec<bool>,
+    south: Vec<bool>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Grid { width, height, east: vec![false; width * height], south: vec![false; width * height] }
+    }
+
+    fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn neighbors(&self, cell: usize) -> Vec<usize> {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let mut neighbors = Vec::with_capacity(4);
+        if x > 0 { neighbors.push(cell - 1); }
+        if x + 1 < self.width { neighbors.push(cell + 1); }
+        if y > 0 { neighbors.push(cell - self.width); }
+        if y + 1 < self.height { neighbors.push(cell + self.width); }
+        neighbors
+    }
+
+    fn connect(&mut self, a: usize, b: usize) {
+        let (low, high) = (a.min(b), a.max(b));
+        if high == low + 1 {
+            self.east[low] = true;
+        } else {
+            self.south[low] = true;
+        }
+    }
+
+    fn is_open(&self, a: usize, b: usize) -> bool {
+        let (low, high) = (a.min(b), a.max(b));
+        if high == low + 1 { self.east[low] } else { self.south[low] }
+    }
+
+    fn openings(&self, cell: usize) -> usize {
+        self.neighbors(cell).into_iter().filter(|&n| self.is_open(cell, n)).count()
+    }
+}
+
+fn recursive_backtracker(grid: &mut Grid, rng: &mut Rng) {
+    let mut visited = vec![false; grid.len()];
+    let start = rng.below(grid.len());
+    let mut stack = vec![start];
+    visited[start] = true;
+
+    while let Some(&cell) = stack.last() {
+        let unvisited: Vec<usize> = grid.neighbors(cell).into_iter().filter(|&n| !visited[n]).collect();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = unvisited[rng.below(unvisited.len())];
+        grid.connect(cell, next);
+        visited[next] = true;
+        stack.push(next);
+    }
+}
+
+fn kruskal(grid: &mut Grid, rng: &mut Rng) {
+    fn find(parents: &mut [usize], cell: usize) -> usize {
+        let mut root = cell;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        // Compresión de caminos
+        let mut cell = cell;
+        while parents[cell] != root {
+            let next = parents[cell];
+            parents[cell] = root;
+            cell = next;
+        }
+        root
+    }
+
+    let mut edges = Vec::new();
+    for cell in 0..grid.len() {
+        if cell % grid.width + 1 < grid.width { edges.push((cell, cell + 1)); }
+        if cell / grid.width + 1 < grid.height { edges.push((cell, cell + grid.width)); }
+    }
+    rng.shuffle(&mut edges);
+
+    let mut parents: Vec<usize> = (0..grid.len()).collect();
+    for (a, b) in edges {
+        let (root_a, root_b) = (find(&mut parents, a), find(&mut parents, b));
+        if root_a != root_b {
+            parents[root_a] = root_b;
+            grid.connect(a, b);
+        }
+    }
+}
+
+fn prim(grid: &mut Grid, rng: &mut Rng) {
+    let mut in_maze = vec![false; grid.len()];
+    let mut in_frontier = vec![false; grid.len()];
+    let mut frontier = Vec::new();
+
+    let mut add = |cell: usize, grid: &Grid, in_maze: &mut Vec<bool>, frontier: &mut Vec<usize>| {
+        in_maze[cell] = true;
+        for n in grid.neighbors(cell) {
+            if !in_maze[n] && !in_frontier[n] {
+                in_frontier[n] = true;
+                frontier.push(n);
+            }
+        }
+    };
+    add(rng.below(grid.len()), grid, &mut in_maze, &mut frontier);
+
+    while !frontier.is_empty() {
+        let cell = frontier.swap_remove(rng.below(frontier.len()));
+        let inside: Vec<usize> = grid.neighbors(cell).into_iter().filter(|&n| in_maze[n]).collect();
+        grid.connect(cell, inside[rng.below(inside.len())]);
+        add(cell, grid, &mut in_maze, &mut frontier);
+    }
+}
+
+fn eller(grid: &mut Grid, rng: &mut Rng) {
+    let width = grid.width;
+    let mut sets: Vec<usize> = vec![0; width];
+    let mut next_set = 1;
+
+    for y in 0..grid.height {
+        let last_row = y + 1 == grid.height;
+        for set in sets.iter_mut().filter(|set| **set == 0) {
+            *set = next_set;
+            next_set += 1;
+        }
+
+        // Unir celdas vecinas de conjuntos distintos; en la última fila, todas
+        for x in 0..width - 1 {
+            if sets[x] != sets[x + 1] && (last_row || rng.chance(0.5)) {
+                grid.connect(y * width + x, y * width + x + 1);
+                let (keep, replace) = (sets[x], sets[x + 1]);
+                for set in sets.iter_mut().filter(|set| **set == replace) {
+                    *set = keep;
+                }
+            }
+        }
+        if last_row {
+            break;
+        }
+
+        // Cada conjunto baja al menos por una celda a la fila siguiente
+        let mut below = vec![0; width];
+        let mut columns: Vec<usize> = (0..width).collect();
+        rng.shuffle(&mut columns);
+        let mut descended: Vec<usize> = Vec::new();
+        for x in columns {
+            let set = sets[x];
+            if !descended.contains(&set) || rng.chance(0.3) {
+                grid.connect(y * width + x, (y + 1) * width + x);
+                below[x] = set;
+                descended.push(set);
+            }
+        }
+        sets = below;
+    }
+}
+
+fn wilson(grid: &mut Grid, rng: &mut Rng) {
+    let mut in_maze = vec![false; grid.len()];
+    in_maze[rng.below(grid.len())] = true;
+
+    let mut order: Vec<usize> = (0..grid.len()).collect();
+    rng.shuffle(&mut order);
+
+    let mut next = vec![0; grid.len()];
+    for start in order {
+        if in_maze[start] {
+            continue;
+        }
+        // Caminata aleatoria hasta tocar el laberinto; guardar solo la última
+        // salida de cada celda borra los ciclos del camino
+        let mut cell = start;
+        while !in_maze[cell] {
+            let neighbors = grid.neighbors(cell);
+            next[cell] = neighbors[rng.below(neighbors.len())];
+            cell = next[cell];
+        }
+
+        let mut cell = start;
+        while !in_maze[cell] {
+            in_maze[cell] = true;
+            grid.connect(cell, next[cell]);
+            cell = next[cell];
+        }
+    }
+}
+
+// Abre parte de los callejones sin salida para crear ciclos
+fn braid(grid: &mut Grid, rng: &mut Rng, factor: f32) {
+    let mut dead_ends: Vec<usize> = (0..grid.len()).filter(|&c| grid.openings(c) == 1).collect();
+    rng.shuffle(&mut dead_ends);
+
+    for cell in dead_ends {
+        if grid.openings(cell) != 1 || !rng.chance(factor) {
+            continue;
+        }
+        let closed: Vec<usize> = grid.neighbors(cell).into_iter().filter(|&n| !grid.is_open(cell, n)).collect();
+        // Preferir unir dos callejones a la vez
+        let dead: Vec<usize> = closed.iter().copied().filter(|&n| grid.openings(n) == 1).collect();
+        let pool = if dead.is_empty() { &closed } else { &dead };
+        if !pool.is_empty() {
+            grid.connect(cell, pool[rng.below(pool.len())]);
+        }
+    }
+}
+
+// Los coleccionables van primero en los callejones, después en cualquier
+// celda; la celda 0 es la del jugador
+fn place_pickups(grid: &Grid, rng: &mut Rng, count: usize) -> Vec<usize> {
+    let (mut dead_ends, mut others): (Vec<usize>, Vec<usize>) =
+        (1..grid.len()).partition(|&c| grid.openings(c) == 1);
+    rng.shuffle(&mut dead_ends);
+    rng.shuffle(&mut others);
+    dead_ends.into_iter().chain(others).take(count).collect()
+}
+
+pub fn generate(config: &GeneratorConfig) -> String {
+    let width = config.width.max(2);
+    let height = config.height.max(2);
+    let mut rng = Rng::new(config.seed);
+    let mut grid = Grid::new(width, height);
+
+    match config.algorithm {
+        Algorithm::RecursiveBacktracker => recursive_backtracker(&mut grid, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut grid, &mut rng),
+        Algorithm::Prim => prim(&mut grid, &mut rng),
+        Algorithm::Eller => eller(&mut grid, &mut rng),
+        Algorithm::Wilson => wilson(&mut grid, &mut rng),
+    }
+    if config.braid > 0.0 {
+        braid(&mut grid, &mut rng, config.braid);
+    }
+
+    let mut items = vec![' '; grid.len()];
+    items[0] = 'S';
+    for (i, cell) in place_pickups(&grid, &mut rng, config.pickups).into_iter().enumerate() {
+        items[cell] = PICKUP_KINDS[i % PICKUP_KINDS.len()];
+    }
+
+    let mut text = format!(
+        "name = \"Aleatorio ({} #{})\"\n[grid]\n",
+        config.algorithm.name(),
+        config.seed
+    );
+    text.push_str(&"+--".repeat(width));
+    text.push_str("+\n");
+    for y in 0..height {
+        let mut cells = String::from("|");
+        let mut floor = String::from("+");
+        for x in 0..width {
+            let cell = y * width + x;
+            cells.push(items[cell]);
+            cells.push(' ');
+            cells.push(if grid.east[cell] { ' ' } else { '|' });
+            floor.push_str(if grid.south[cell] { "  +" } else { "--+" });
+        }
+        text.push_str(&cells);
+        text.push('\n');
+        text.push_str(&floor);
+        text.push('\n');
+    }
+    text
+}
+
+pub fn generate_level(config: &GeneratorConfig) -> Result<Level, LevelError> {
+    parse_level(&generate(config), "aleatorio")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    fn carve(algorithm: Algorithm, width: usize, height: usize, seed: u64) -> Grid {
+        let mut grid = Grid::new(width, height);
+        let mut rng = Rng::new(seed);
+        match algorithm {
+            Algorithm::RecursiveBacktracker => recursive_backtracker(&mut grid, &mut rng),
+            Algorithm::Kruskal => kruskal(&mut grid, &mut rng),
+            Algorithm::Prim => prim(&mut grid, &mut rng),
+            Algorithm::Eller => eller(&mut grid, &mut rng),
+            Algorithm::Wilson => wilson(&mut grid, &mut rng),
+        }
+        grid
+    }
+
+    fn passages(grid: &Grid) -> usize {
+        grid.east.iter().chain(&grid.south).filter(|&&open| open).count()
+    }
+
+    fn connected_cells(grid: &Grid) -> usize {
+        let mut seen = vec![false; grid.len()];
+        let mut stack = vec![0];
+        seen[0] = true;
+        while let Some(cell) = stack.pop() {
+            for n in grid.neighbors(cell) {
+                if grid.is_open(cell, n) && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        seen.iter().filter(|&&reached| reached).count()
+    }
+
+    #[test]
+    fn every_algorithm_carves_a_perfect_maze() {
+        for algorithm in Algorithm::ALL {
+            for (width, height, seed) in [(2, 2, 1), (5, 4, 7), (12, 9, 42), (16, 12, 0xDEAD_BEEF)] {
+                let grid = carve(algorithm, width, height, seed);
+                // Conexo y con una pared menos que un árbol: sin ciclos
+                assert_eq!(connected_cells(&grid), grid.len(), "{} {}x{}", algorithm.name(), width, height);
+                assert_eq!(passages(&grid), grid.len() - 1, "{} {}x{}", algorithm.name(), width, height);
+                // Nunca se abre hacia afuera de la grilla
+                for y in 0..height {
+                    assert!(!grid.east[y * width + width - 1]);
+                }
+                for x in 0..width {
+                    assert!(!grid.south[(height - 1) * width + x]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        for stage in 0..Algorithm::ALL.len() {
+            let config = GeneratorConfig::endless(1234, stage);
+            assert_eq!(generate(&config), generate(&config));
+        }
+        assert_ne!(generate(&GeneratorConfig::endless(1, 0)), generate(&GeneratorConfig::endless(2, 0)));
+    }
+
+    #[test]
+    fn braiding_only_adds_passages() {
+        let mut grid = carve(Algorithm::RecursiveBacktracker, 10, 8, 3);
+        let before = passages(&grid);
+        braid(&mut grid, &mut Rng::new(3), 1.0);
+        assert!(passages(&grid) > before);
+        assert_eq!(connected_cells(&grid), grid.len());
+        assert!((0..grid.len()).all(|cell| grid.openings(cell) > 1));
+    }
+
+    #[test]
+    fn generated_levels_load_with_spawn_and_pickups() {
+        for stage in [0, 3, 9] {
+            let config = GeneratorConfig::endless(99, stage);
+            let level = generate_level(&config).unwrap();
+            assert_eq!(level.spawns.len(), 1);
+            let pickups = level.maze.iter().flatten().filter(|cell| matches!(cell, Cell::Pickup { .. })).count();
+            assert_eq!(pickups, config.pickups);
+        }
+    }
+
+    #[test]
+    fn rng_stays_in_range() {
+        let mut rng = Rng::new(5);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+        assert!(!(0..100).any(|_| rng.chance(0.0)));
+        assert!((0..100).all(|_| rng.chance(1.0)));
+        let mut items: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
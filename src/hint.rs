@@ -0,0 +1,100 @@
+// hint.rs
+//
+// Pista opcional: camino más corto desde el jugador hasta el coleccionable
+// pendiente más cercano, dibujado en el minimapa y como una flecha discreta
+// arriba de la pantalla. Cada vez que se activa suma tiempo de penalización.
+
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::framebuffer::Framebuffer;
+use crate::line::line;
+use crate::maze::Maze;
+use crate::pathfinding::{smooth_path, world_to_cell, PathOptions, Pathfinder};
+use crate::player::{Player, PLAYER_RADIUS};
+use crate::sprites::Sprite;
+
+// Segundos que se suman al tiempo del nivel por cada activación
+pub const HINT_PENALTY: f32 = 15.0;
+
+pub const HINT_COLOR: Color = Color::new(255, 215, 0, 255);
+const ARROW_LENGTH: f32 = 36.0;
+
+pub struct Hint {
+    pub enabled: bool,
+    pub uses: u32,
+    pathfinder: Pathfinder,
+    waypoints: Vec<Vector2>, // puntos del mundo desde el jugador hasta el coleccionable
+}
+
+impl Hint {
+    pub fn new(maze: &Maze) -> Self {
+        Hint { enabled: false, uses: 0, pathfinder: Pathfinder::for_maze(maze), waypoints: Vec::new() }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            self.uses += 1;
+        } else {
+            self.waypoints.clear();
+        }
+    }
+
+    pub fn penalty(&self) -> f32 {
+        self.uses as f32 * HINT_PENALTY
+    }
+
+    pub fn update(&mut self, maze: &Maze, player: &Player, sprites: &[Sprite], block_size: usize) {
+        self.waypoints.clear();
+        if !self.enabled {
+            return;
+        }
+
+        let targets: Vec<(usize, usize)> = sprites
+            .iter()
+            .filter(|sprite| !sprite.collected)
+            .map(|sprite| world_to_cell(sprite.pos, block_size))
+            .collect();
+        let start = world_to_cell(player.pos, block_size);
+        // El jugador puede abrir puertas, así que la pista pasa por ellas
+        // igual que el validador
+        let options = PathOptions { doors: true, ..Default::default() };
+        let Some(path) = self.pathfinder.nearest(maze, start, options, |cell| targets.contains(&cell)) else {
+            return;
+        };
+
+        self.waypoints = smooth_path(maze, &path, PLAYER_RADIUS, block_size, options);
+        // El camino arranca en el jugador, no en el centro de su celda
+        if let Some(first) = self.waypoints.first_mut() {
+            *first = player.pos;
+        }
+    }
+
+    // Camino en puntos del mundo, para que lo dibuje el minimapa
+    pub fn path(&self) -> &[Vector2] {
+        &self.waypoints
+    }
+
+    // Flecha arriba al centro: hacia arriba es "de frente"
+    pub fn draw_arrow(&self, framebuffer: &mut Framebuffer, player: &Player) {
+        let Some(target) = self.waypoints.get(1) else { return };
+        let relative = (target.y - player.pos.y).atan2(target.x - player.pos.x) - player.a;
+        let direction = Vector2::new(relative.sin(), -relative.cos());
+
+        let center = Vector2::new(framebuffer.width as f32 / 2.0, 60.0);
+        let tip = center + direction * (ARROW_LENGTH / 2.0);
+        let tail = center - direction * (ARROW_LENGTH / 2.0);
+
+        framebuffer.set_current_color(HINT_COLOR);
+        for offset in [-1.0, 0.0, 1.0] {
+            let side = Vector2::new(-direction.y, direction.x) * offset;
+            line(framebuffer, tail + side, tip + side);
+        }
+        for turn in [-PI * 0.8, PI * 0.8] {
+            let (sin, cos) = turn.sin_cos();
+            let barb = Vector2::new(direction.x * cos - direction.y * sin, direction.x * sin + direction.y * cos);
+            line(framebuffer, tip, tip + barb * (ARROW_LENGTH / 3.0));
+        }
+    }
+}
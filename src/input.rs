@@ -0,0 +1,358 @@
+// input.rs
+//
+// Capa de acciones entre los dispositivos y el juego. El juego pregunta por
+// acciones (avanzar, girar, usar...) y cada acción tiene varias teclas,
+// botones del mouse, botones o ejes del control asignados. Las asignaciones
+// se guardan con el resto de las opciones y se cambian desde la pantalla de
+// controles.
+
+use raylib::prelude::*;
+
+// Control del que se leen botones y ejes
+pub const GAMEPAD: i32 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    LookUp,
+    LookDown,
+    Run,
+    Jump,
+    Crouch,
+    Use,
+    Fire,
+    WeaponPistol,
+    WeaponLauncher,
+    Pause,
+    Map,
+    MapZoomIn,
+    MapZoomOut,
+    Hint,
+    Restart,
+    Mute,
+}
+
+impl Action {
+    pub const ALL: [Action; 22] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::LookUp,
+        Action::LookDown,
+        Action::Run,
+        Action::Jump,
+        Action::Crouch,
+        Action::Use,
+        Action::Fire,
+        Action::WeaponPistol,
+        Action::WeaponLauncher,
+        Action::Pause,
+        Action::Map,
+        Action::MapZoomIn,
+        Action::MapZoomOut,
+        Action::Hint,
+        Action::Restart,
+        Action::Mute,
+    ];
+
+    // Clave en el archivo de configuración (`bind.<nombre>`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::LookUp => "look_up",
+            Action::LookDown => "look_down",
+            Action::Run => "run",
+            Action::Jump => "jump",
+            Action::Crouch => "crouch",
+            Action::Use => "use",
+            Action::Fire => "fire",
+            Action::WeaponPistol => "weapon_pistol",
+            Action::WeaponLauncher => "weapon_launcher",
+            Action::Pause => "pause",
+            Action::Map => "map",
+            Action::MapZoomIn => "map_zoom_in",
+            Action::MapZoomOut => "map_zoom_out",
+            Action::Hint => "hint",
+            Action::Restart => "restart",
+            Action::Mute => "mute",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    // Texto de la pantalla de controles
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Avanzar",
+            Action::MoveBack => "Retroceder",
+            Action::StrafeLeft => "Moverse a la izquierda",
+            Action::StrafeRight => "Moverse a la derecha",
+            Action::TurnLeft => "Girar a la izquierda",
+            Action::TurnRight => "Girar a la derecha",
+            Action::LookUp => "Mirar arriba",
+            Action::LookDown => "Mirar abajo",
+            Action::Run => "Correr (mantener)",
+            Action::Jump => "Saltar",
+            Action::Crouch => "Agacharse (mantener)",
+            Action::Use => "Usar (abrir puertas)",
+            Action::Fire => "Disparar",
+            Action::WeaponPistol => "Elegir pistola",
+            Action::WeaponLauncher => "Elegir lanzador",
+            Action::Pause => "Pausa",
+            Action::Map => "Mostrar minimapa",
+            Action::MapZoomIn => "Acercar minimapa",
+            Action::MapZoomOut => "Alejar minimapa",
+            Action::Hint => "Pista",
+            Action::Restart => "Reiniciar nivel",
+            Action::Mute => "Silenciar música",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    Button(GamepadButton),
+    // Un eje del control en una dirección; `positive` elige la mitad del eje
+    Axis { axis: GamepadAxis, positive: bool },
+}
+
+const MOUSE_BUTTONS: [(MouseButton, &str); 3] = [
+    (MouseButton::MOUSE_BUTTON_LEFT, "MOUSE_LEFT"),
+    (MouseButton::MOUSE_BUTTON_RIGHT, "MOUSE_RIGHT"),
+    (MouseButton::MOUSE_BUTTON_MIDDLE, "MOUSE_MIDDLE"),
+];
+
+const GAMEPAD_BUTTONS: [(GamepadButton, &str); 16] = [
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP, "PAD_UP"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT, "PAD_RIGHT"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN, "PAD_DOWN"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT, "PAD_LEFT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP, "PAD_Y"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, "PAD_B"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN, "PAD_A"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT, "PAD_X"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1, "PAD_LB"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2, "PAD_LT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1, "PAD_RB"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2, "PAD_RT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT, "PAD_SELECT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT, "PAD_START"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB, "PAD_L3"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB, "PAD_R3"),
+];
+
+// Solo los sticks; los gatillos analógicos se asignan como botones
+const GAMEPAD_AXES: [(GamepadAxis, &str); 4] = [
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_X, "PAD_LEFT_X"),
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_Y, "PAD_LEFT_Y"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_X, "PAD_RIGHT_X"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_Y, "PAD_RIGHT_Y"),
+];
+
+impl Binding {
+    // Nombre en el archivo de configuración y en la pantalla de controles:
+    // `W`, `SPACE`, `MOUSE_LEFT`, `PAD_A`, `PAD_LEFT_Y-`...
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key).trim_start_matches("KEY_").to_string(),
+            Binding::Mouse(button) => lookup(&MOUSE_BUTTONS, button).unwrap_or("MOUSE_?").to_string(),
+            Binding::Button(button) => lookup(&GAMEPAD_BUTTONS, button).unwrap_or("PAD_?").to_string(),
+            Binding::Axis { axis, positive } => {
+                format!("{}{}", lookup(&GAMEPAD_AXES, axis).unwrap_or("PAD_?"), if positive { '+' } else { '-' })
+            }
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        if let Some(button) = find_named(&MOUSE_BUTTONS, name) {
+            return Some(Binding::Mouse(button));
+        }
+        if let Some(button) = find_named(&GAMEPAD_BUTTONS, name) {
+            return Some(Binding::Button(button));
+        }
+        if let Some((axis, positive)) = name.strip_suffix('+').map(|axis| (axis, true))
+            .or_else(|| name.strip_suffix('-').map(|axis| (axis, false)))
+            && let Some(axis) = find_named(&GAMEPAD_AXES, axis)
+        {
+            return Some(Binding::Axis { axis, positive });
+        }
+        // Los códigos de tecla de raylib llegan hasta KEY_KB_MENU (348)
+        (0..=348).filter_map(key_from_i32).map(Binding::Key).find(|binding| binding.name() == name)
+    }
+
+    // Cuánto está apretada, de 0 a 1; los ejes por debajo de la zona
+    // muerta cuentan como sueltos
+    fn value(self, rl: &RaylibHandle, deadzone: f32) -> f32 {
+        let pressed = |down: bool| if down { 1.0 } else { 0.0 };
+        match self {
+            Binding::Key(key) => pressed(rl.is_key_down(key)),
+            Binding::Mouse(button) => pressed(rl.is_mouse_button_down(button)),
+            Binding::Button(button) => {
+                pressed(rl.is_gamepad_available(GAMEPAD) && rl.is_gamepad_button_down(GAMEPAD, button))
+            }
+            Binding::Axis { axis, positive } => {
+                if !rl.is_gamepad_available(GAMEPAD) {
+                    return 0.0;
+                }
+                let movement = rl.get_gamepad_axis_movement(GAMEPAD, axis);
+                let movement = if positive { movement } else { -movement };
+                if movement > deadzone { movement.min(1.0) } else { 0.0 }
+            }
+        }
+    }
+
+    // Los ejes no tienen "recién apretado", solo sirven para acciones que
+    // se mantienen
+    fn pressed(self, rl: &RaylibHandle) -> bool {
+        match self {
+            Binding::Key(key) => rl.is_key_pressed(key),
+            Binding::Mouse(button) => rl.is_mouse_button_pressed(button),
+            Binding::Button(button) => rl.is_gamepad_available(GAMEPAD) && rl.is_gamepad_button_pressed(GAMEPAD, button),
+            Binding::Axis { .. } => false,
+        }
+    }
+
+    fn is_analog(self) -> bool {
+        matches!(self, Binding::Axis { .. })
+    }
+
+    // Primera tecla, botón o eje que se aprieta en este frame, para la
+    // pantalla de controles. ESC queda reservado para cancelar.
+    pub fn capture(rl: &mut RaylibHandle) -> Option<Binding> {
+        if let Some(key) = rl.get_key_pressed() {
+            return (key != KeyboardKey::KEY_ESCAPE).then_some(Binding::Key(key));
+        }
+        if let Some((button, _)) = MOUSE_BUTTONS.iter().find(|(button, _)| rl.is_mouse_button_pressed(*button)) {
+            return Some(Binding::Mouse(*button));
+        }
+        if !rl.is_gamepad_available(GAMEPAD) {
+            return None;
+        }
+        if let Some((button, _)) = GAMEPAD_BUTTONS.iter().find(|(button, _)| rl.is_gamepad_button_pressed(GAMEPAD, *button)) {
+            return Some(Binding::Button(*button));
+        }
+        GAMEPAD_AXES.iter().find_map(|(axis, _)| {
+            let movement = rl.get_gamepad_axis_movement(GAMEPAD, *axis);
+            (movement.abs() > 0.5).then_some(Binding::Axis { axis: *axis, positive: movement > 0.0 })
+        })
+    }
+}
+
+fn lookup<T: PartialEq + Copy>(list: &[(T, &'static str)], item: T) -> Option<&'static str> {
+    list.iter().find(|(candidate, _)| *candidate == item).map(|(_, name)| *name)
+}
+
+fn find_named<T: Copy>(list: &[(T, &'static str)], name: &str) -> Option<T> {
+    list.iter().find(|(_, candidate)| *candidate == name).map(|(item, _)| *item)
+}
+
+// Asignaciones de todas las acciones, en el orden de `Action::ALL`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    actions: Vec<Vec<Binding>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadAxis::*;
+        use KeyboardKey::*;
+        let key = Binding::Key;
+        let pad = |name: &str| Binding::from_name(name).expect("botón del control por defecto");
+        let axis = |axis, positive| Binding::Axis { axis, positive };
+
+        let actions = Action::ALL
+            .iter()
+            .map(|action| match action {
+                Action::MoveForward => vec![key(KEY_W), key(KEY_UP), axis(GAMEPAD_AXIS_LEFT_Y, false)],
+                Action::MoveBack => vec![key(KEY_S), key(KEY_DOWN), axis(GAMEPAD_AXIS_LEFT_Y, true)],
+                Action::StrafeLeft => vec![key(KEY_A), axis(GAMEPAD_AXIS_LEFT_X, false)],
+                Action::StrafeRight => vec![key(KEY_D), axis(GAMEPAD_AXIS_LEFT_X, true)],
+                Action::TurnLeft => vec![key(KEY_LEFT), axis(GAMEPAD_AXIS_RIGHT_X, false)],
+                Action::TurnRight => vec![key(KEY_RIGHT), axis(GAMEPAD_AXIS_RIGHT_X, true)],
+                Action::LookUp => vec![key(KEY_PAGE_UP), axis(GAMEPAD_AXIS_RIGHT_Y, false)],
+                Action::LookDown => vec![key(KEY_PAGE_DOWN), axis(GAMEPAD_AXIS_RIGHT_Y, true)],
+                Action::Run => vec![key(KEY_LEFT_SHIFT), pad("PAD_L3")],
+                Action::Jump => vec![key(KEY_SPACE), pad("PAD_A")],
+                Action::Crouch => vec![key(KEY_LEFT_CONTROL), key(KEY_C), pad("PAD_B")],
+                Action::Use => vec![key(KEY_E), pad("PAD_X")],
+                Action::Fire => vec![Binding::Mouse(MouseButton::MOUSE_BUTTON_LEFT), key(KEY_F), pad("PAD_RT")],
+                Action::WeaponPistol => vec![key(KEY_ONE), pad("PAD_LB")],
+                Action::WeaponLauncher => vec![key(KEY_TWO), pad("PAD_RB")],
+                // ESC siempre pausa, aunque no esté en la lista
+                Action::Pause => vec![key(KEY_P), pad("PAD_START")],
+                Action::Map => vec![key(KEY_TAB), pad("PAD_SELECT")],
+                Action::MapZoomIn => vec![key(KEY_EQUAL), key(KEY_KP_ADD), pad("PAD_UP")],
+                Action::MapZoomOut => vec![key(KEY_MINUS), key(KEY_KP_SUBTRACT), pad("PAD_DOWN")],
+                Action::Hint => vec![key(KEY_H), pad("PAD_Y")],
+                Action::Restart => vec![key(KEY_R)],
+                Action::Mute => vec![key(KEY_M)],
+            })
+            .collect();
+        Bindings { actions }
+    }
+}
+
+impl Bindings {
+    fn index(action: Action) -> usize {
+        Action::ALL.iter().position(|&candidate| candidate == action).unwrap_or(0)
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        &self.actions[Self::index(action)]
+    }
+
+    pub fn set(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.actions[Self::index(action)] = bindings;
+    }
+
+    // Una tecla solo puede hacer una cosa: se quita de la acción que la tenía
+    pub fn add(&mut self, action: Action, binding: Binding) {
+        for bindings in &mut self.actions {
+            bindings.retain(|&existing| existing != binding);
+        }
+        self.actions[Self::index(action)].push(binding);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.actions[Self::index(action)].clear();
+    }
+
+    // Lista para la pantalla de controles y el archivo de configuración
+    pub fn names(&self, action: Action) -> String {
+        self.get(action).iter().map(|binding| binding.name()).collect::<Vec<_>>().join(", ")
+    }
+
+    pub fn down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.get(action).iter().any(|binding| !binding.is_analog() && binding.value(rl, 0.0) > 0.0)
+    }
+
+    pub fn pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.get(action).iter().any(|binding| binding.pressed(rl))
+    }
+
+    // Lo más apretado de los ejes asignados, de 0 a 1
+    pub fn axis(&self, rl: &RaylibHandle, action: Action, deadzone: f32) -> f32 {
+        self.get(action)
+            .iter()
+            .filter(|binding| binding.is_analog())
+            .map(|binding| binding.value(rl, deadzone))
+            .fold(0.0, f32::max)
+    }
+}
@@ -4,16 +4,33 @@
 mod line;
 mod framebuffer;
 mod maze;
+mod minimap;
+mod camera;
+mod cell;
+mod collision;
 mod caster;
 mod player;
 mod textures;
+mod audio;
+mod generator;
+mod validator;
+mod pathfinding;
+mod hint;
+mod enemy;
+mod weapon;
+mod play;
+mod game;
+mod campaign;
+mod config;
+mod storage;
+mod input;
 pub mod sprites;
 
 use line::line;
-use maze::{Maze, load_maze};
+use maze::{Maze, Level, load_maze};
 use caster::{cast_ray, Intersect};
 use framebuffer::Framebuffer;
-use player::{Player, process_events};
+use player::{project_height, Player, process_events, PLAYER_RADIUS};
 
 use raylib::prelude::*;
 use std::thread;
@@ -21,108 +38,116 @@ use std::time::Duration;
 use std::f32::consts::PI;
 
 use crate::maze::is_wall;
+use crate::cell::Cell;
 use crate::sprites::{Sprite, render_sprites};
 
-use std::fs::File;
-use std::io::BufReader;
-use rodio::{Decoder, OutputStream, source::Source};
+use crate::textures::{TextureManager, SKY_TEXTURE, FLOOR_TEXTURE};
+use crate::audio::Audio;
+use crate::validator::{validate, check_exit_targets};
+use crate::game::Game;
+use crate::campaign::Campaign;
+use crate::config::Settings;
 
-use crate::textures::TextureManager;
+const BLOCK_SIZE: usize = 100;
+// Distancia a la que el jugador recoge un coleccionable
+const PICKUP_RANGE: f32 = 80.0;
 
-fn cell_to_texture_color(
-    texture_cache: &TextureManager,
-    cell: char,
-    tx: u32,
-    ty: u32,
-) -> Color {
-    texture_cache.get_pixel_color(cell, tx, ty)
-}
+const SPRITE_HUM: &str = "sounds/hum.ogg";
+const COLLECT_SOUND: &str = "sounds/collect.ogg";
+const SHOT_SOUND: &str = "sounds/shot.ogg";
+
+const MENU_MUSIC: &[&str] = &["sounds/menu.mp3"];
+const VICTORY_MUSIC: &[&str] = &["sounds/victory.mp3"];
+
+const CAMPAIGN_FILE: &str = "campaign.txt";
 
-fn cell_to_color(cell: char) -> Color {
-    match cell {
-        '+' => Color::BLUEVIOLET,
-        '-' => Color::VIOLET,
-        '|' => Color::VIOLET,
-        'g' => Color::GREEN,
-        'p' => Color::RED,
-        _ => Color::WHITE,
+// Un nivel que no se puede completar no se juega; los avisos solo se informan
+fn playable(level: Level, source: &str) -> Option<Level> {
+    let issues = validate(&level, BLOCK_SIZE, PLAYER_RADIUS, PICKUP_RANGE);
+    for issue in &issues {
+        eprintln!("{}: {}", source, issue);
     }
+    if issues.iter().any(|issue| issue.is_fatal()) { None } else { Some(level) }
 }
 
-fn draw_cell(
-    framebuffer: &mut Framebuffer,
-    xo: usize,
-    yo: usize,
-    block_size: usize,
-    cell: char,
-    texture_cache: &TextureManager,
-) {
-    if cell == ' ' { return; }
-
-    if let Some(image) = texture_cache.images.get(&cell) {
-        let tex_w = image.width as usize;
-        let tex_h = image.height as usize;
-        for x in 0..block_size {
-            for y in 0..block_size {
-                let tx = (x * tex_w) / block_size;
-                let ty = (y * tex_h) / block_size;
-                let color = texture_cache.get_pixel_color(cell, tx as u32, ty as u32);
-                framebuffer.set_current_color(color);
-                framebuffer.set_pixel((xo + x) as u32, (yo + y) as u32);
-            }
+// `--check nivel...`: valida los niveles (y sus siguientes) sin abrir la
+// ventana; sin argumentos revisa los de la campaña. Devuelve false si alguno
+// no se puede completar.
+fn check_levels(files: &[String]) -> bool {
+    let campaign = match Campaign::load(CAMPAIGN_FILE) {
+        Ok(campaign) => Some(campaign),
+        Err(e) => {
+            println!("{}: error: {}", CAMPAIGN_FILE, e);
+            None
         }
-    } else {
-        framebuffer.set_current_color(cell_to_color(cell));
-        for x in xo..xo + block_size {
-            for y in yo..yo + block_size {
-                framebuffer.set_pixel(x as u32, y as u32);
+    };
+    let files: Vec<String> = match &campaign {
+        _ if !files.is_empty() => files.to_vec(),
+        Some(campaign) => campaign.files().map(str::to_string).collect(),
+        None => return false,
+    };
+
+    let mut ok = true;
+    for file in &files {
+        let level = match load_maze(file) {
+            Ok(level) => level,
+            Err(e) => {
+                println!("{}: error: {}", file, e);
+                ok = false;
+                continue;
+            }
+        };
+
+        let mut issues = validate(&level, BLOCK_SIZE, PLAYER_RADIUS, PICKUP_RANGE);
+        let next = match &campaign {
+            Some(campaign) => campaign.next_level(file, level.meta.next.as_deref()),
+            None => level.meta.next.clone(),
+        };
+        if let Some(next) = &next {
+            match load_maze(next) {
+                Ok(next_level) => issues.extend(check_exit_targets(&level, &next_level)),
+                Err(e) => {
+                    println!("{}: error: el nivel siguiente {} no carga: {}", file, next, e);
+                    ok = false;
+                }
             }
         }
-    }
-}
 
-pub fn render_maze(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-    texture_cache: &TextureManager,
-) {
-    for (row_index, row) in maze.iter().enumerate() {
-        for (col_index, &cell) in row.iter().enumerate() {
-            let xo = col_index * block_size;
-            let yo = row_index * block_size;
-            draw_cell(framebuffer, xo, yo, block_size, cell, texture_cache);
+        if issues.is_empty() {
+            println!("{}: ok", file);
+        }
+        for issue in &issues {
+            println!("{}: {}", file, issue);
         }
+        ok &= !issues.iter().any(|issue| issue.is_fatal());
     }
+    ok
+}
 
-    if let Some(image) = texture_cache.images.get(&'p') {
-        let tex_w = image.width as usize;
-        let tex_h = image.height as usize;
-        let scale = 10;
-        let px = (player.pos.x / 5.0) as usize;
-        let py = (player.pos.y / 5.0) as usize;
-
-        for x in 0..scale {
-            for y in 0..scale {
-                let tx = (x * tex_w) / scale;
-                let ty = (y * tex_h) / scale;
-                let color = texture_cache.get_pixel_color('p', tx as u32, ty as u32);
-                if color.a > 0 {
-                    framebuffer.set_current_color(color);
-                    framebuffer.set_pixel((px + x) as u32, (py + y) as u32);
-                }
-            }
-        }
-    } else {
-        framebuffer.set_current_color(Color::RED);
-        framebuffer.set_pixel((player.pos.x / 5.0) as u32, (player.pos.y / 5.0) as u32);
+fn cell_to_texture_color(
+    texture_cache: &TextureManager,
+    cell: Cell,
+    tx: u32,
+    ty: u32,
+) -> Color {
+    match cell.texture() {
+        // tx y ty vienen en una grilla de 128x128; se escalan al tamaño real
+        // de la imagen para que los tiles de Tiled (32x32, 64x64...) se vean completos
+        Some(texture) => match texture_cache.images.get(&texture) {
+            Some(image) => texture_cache.get_pixel_color(
+                texture,
+                tx * image.width as u32 / 128,
+                ty * image.height as u32 / 128,
+            ),
+            None => texture_cache.get_pixel_color(texture, tx, ty),
+        },
+        None => cell.minimap_color().unwrap_or(Color::WHITE),
     }
 }
 
 fn render_world(
     framebuffer: &mut Framebuffer,
-    maze: &Maze,
+    level: &Level,
     block_size: usize,
     player: &Player,
     texture_cache: &TextureManager,
@@ -130,34 +155,100 @@ fn render_world(
     let num_rays = framebuffer.width;
     let hh = framebuffer.height as f32 / 2.0;
     let mut z_buffer = vec![f32::INFINITY; num_rays as usize];
+    let meta = &level.meta;
+    let distance_to_projection_plane = 70.0;
+    // Alto en píxeles de una pared a distancia 1; la mirada vertical corre
+    // el horizonte y saltar o agacharse cambia desde qué altura se mira
+    let scale = hh * distance_to_projection_plane;
+    let horizon = player.horizon(framebuffer.height);
+    let horizon_row = horizon.clamp(0.0, framebuffer.height as f32) as u32;
+    let eye = player.eye_height();
+
+    let sky_image = meta.sky_texture.as_ref().and(texture_cache.images.get(&SKY_TEXTURE));
+    let floor_image = meta.floor_texture.as_ref().and(texture_cache.images.get(&FLOOR_TEXTURE));
+    let bs = block_size as f32;
+
+    // Color del tile `key` en el punto del mundo (wx, wy), repitiendo la imagen en cada celda
+    let tile_color = |key: char, wx: f32, wy: f32| {
+        let (w, h) = texture_cache.images.get(&key).map_or((1, 1), |image| (image.width, image.height));
+        let tx = wx.rem_euclid(bs) / bs * w as f32;
+        let ty = wy.rem_euclid(bs) / bs * h as f32;
+        texture_cache.get_pixel_color(key, tx as u32, ty as u32)
+    };
 
     for i in 0..framebuffer.width {
-        framebuffer.set_current_color(Color::SKYBLUE);
-        for j in 0..(framebuffer.height / 2) { framebuffer.set_pixel(i, j); }
-        framebuffer.set_current_color(Color::LIGHTGREEN);
-        for j in (framebuffer.height / 2)..framebuffer.height { framebuffer.set_pixel(i, j); }
+        let a = player.view_angle() - (player.fov / 2.0) + (player.fov * i as f32 / num_rays as f32);
+
+        for j in 0..horizon_row {
+            // Techo por celda (mapas de Tiled) con la misma proyección que el piso
+            let ceiling_distance = (1.0 - eye) * scale / (horizon - j as f32 - 0.5);
+            let wx = player.pos.x + a.cos() * ceiling_distance;
+            let wy = player.pos.y + a.sin() * ceiling_distance;
+            let ceiling = level.ceiling_tile((wx / bs) as usize, (wy / bs) as usize);
+
+            let color = if let Some(key) = ceiling.filter(|_| wx >= 0.0 && wy >= 0.0) {
+                meta.fogged(level.lit(tile_color(key, wx, wy), wx / bs, wy / bs), ceiling_distance)
+            } else if let Some(image) = sky_image {
+                // El cielo es un panorama que gira con el ángulo de la vista
+                let tx = a.rem_euclid(2.0 * PI) / (2.0 * PI) * image.width as f32;
+                let ty = ((j as f32 - horizon) / hh + 1.0).clamp(0.0, 0.999) * image.height as f32;
+                texture_cache.get_pixel_color(SKY_TEXTURE, tx as u32, ty as u32)
+            } else {
+                meta.sky
+            };
+            framebuffer.set_current_color(color);
+            framebuffer.set_pixel(i, j);
+        }
+
+        for j in horizon_row..framebuffer.height {
+            // Distancia al punto del piso que se ve en esta fila, con la misma
+            // proyección que usan las paredes
+            let floor_distance = eye * scale / (j as f32 - horizon + 0.5);
+            let wx = player.pos.x + a.cos() * floor_distance;
+            let wy = player.pos.y + a.sin() * floor_distance;
+            let (col, row) = ((wx / bs) as usize, (wy / bs) as usize);
+            let tile = level.floor_tile(col, row);
+            let hazard = level.maze.get(row).and_then(|cells| cells.get(col)).and_then(|cell| match cell {
+                Cell::Hazard { kind } => Some(*kind),
+                _ => None,
+            });
+
+            // Las trampas se pintan encima de cualquier textura del piso
+            let color = match (hazard.filter(|_| wx >= 0.0 && wy >= 0.0), tile.filter(|_| wx >= 0.0 && wy >= 0.0), floor_image) {
+                (Some(kind), _, _) => kind.color(),
+                (None, Some(key), _) => tile_color(key, wx, wy),
+                (None, None, Some(_)) => tile_color(FLOOR_TEXTURE, wx, wy),
+                (None, None, None) => meta.floor,
+            };
+            framebuffer.set_current_color(meta.fogged(level.lit(color, wx / bs, wy / bs), floor_distance));
+            framebuffer.set_pixel(i, j);
+        }
     }
 
     framebuffer.set_current_color(Color::WHITESMOKE);
 
     for i in 0..num_rays {
         let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false);
+        let a = player.view_angle() - (player.fov / 2.0) + (player.fov * current_ray);
+        let intersect = cast_ray(framebuffer, &level.maze, player, a, block_size, false);
 
         let distance_to_wall = intersect.distance;
         z_buffer[i as usize] = distance_to_wall;
 
-        let distance_to_projection_plane = 70.0;
-        let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
-        let stake_top = (hh - (stake_height / 2.0)) as usize;
-        let stake_bottom = (hh + (stake_height / 2.0)) as usize;
+        let stake_top = project_height(horizon, eye, 1.0, scale, distance_to_wall);
+        let stake_bottom = project_height(horizon, eye, 0.0, scale, distance_to_wall);
+
+        let hit_x = (player.pos.x + a.cos() * distance_to_wall) / bs;
+        let hit_y = (player.pos.y + a.sin() * distance_to_wall) / bs;
 
-        for y in stake_top..stake_bottom {
-            let ty = (y as f32 - stake_top as f32) / (stake_bottom as f32 - stake_top as f32) * 128.0;
+        // La textura se estira sobre toda la pared aunque se salga de la pantalla
+        let first_row = stake_top.max(0.0) as u32;
+        let last_row = stake_bottom.min(framebuffer.height as f32).max(0.0) as u32;
+        for y in first_row..last_row {
+            let ty = (y as f32 - stake_top) / (stake_bottom - stake_top) * 128.0;
             let color = cell_to_texture_color(texture_cache, intersect.impact, intersect.tx as u32, ty as u32);
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(i, y as u32);
+            framebuffer.set_current_color(meta.fogged(level.lit(color, hit_x, hit_y), distance_to_wall));
+            framebuffer.set_pixel(i, y);
         }
     }
 
@@ -165,173 +256,45 @@ fn render_world(
 }
 
 fn main() {
-    let window_width = 1300;
-    let window_height = 900;
-    let block_size = 100;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--check") {
+        let ok = check_levels(&args[1..]);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    // `--seed N`: el modo infinito usa esa semilla en lugar de una al azar
+    let endless_seed = match args.iter().position(|arg| arg == "--seed") {
+        Some(index) => match args.get(index + 1).and_then(|seed| seed.parse().ok()) {
+            Some(seed) => Some(seed),
+            None => {
+                eprintln!("--seed espera un número");
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let block_size = BLOCK_SIZE;
 
-    let stream_handle = rodio::OutputStreamBuilder::open_default_stream().unwrap();
-    let file = BufReader::new(File::open("sounds/Karma.mp3").unwrap());
-    let _sink = rodio::play(&stream_handle.mixer(), file).unwrap();
+    let audio = Audio::new(block_size);
 
+    let settings = Settings::load();
     let (mut window, raylib_thread) = raylib::init()
-        .size(window_width, window_height)
+        .size(settings.width, settings.height)
         .title("Raycaster Example")
+        .vsync()
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
 
-    window.hide_cursor();
-    window.disable_cursor();
+    // ESC abre la pausa en lugar de cerrar la ventana
+    window.set_exit_key(None);
 
-    let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32);
+    let (render_width, render_height) = settings.render_size();
+    let mut framebuffer = Framebuffer::new(render_width, render_height);
     framebuffer.set_background_color(Color::new(50, 50, 100, 255));
 
     let texture_cache = TextureManager::new(&mut window, &raylib_thread);
 
-    let start_screen = window.load_texture(&raylib_thread, "assets/start.png").unwrap();
-    let end_screen = window.load_texture(&raylib_thread, "assets/end.png").unwrap();
-
-    fn run_level(
-        window: &mut RaylibHandle,
-        raylib_thread: &RaylibThread,
-        framebuffer: &mut Framebuffer,
-        texture_cache: &TextureManager,
-        block_size: usize,
-        maze_file: &str,
-    ) -> bool {
-        let maze = load_maze(maze_file);
-        let mut player = Player::new(Vector2::new(150.0, 150.0), PI / 3.0, PI / 3.0);
-
-        let mut sprites = Vec::new();
-        for (row_index, row) in maze.iter().enumerate() {
-            for (col_index, &cell) in row.iter().enumerate() {
-                match cell {
-                    'A' | 'B' | 'G' => {
-                        let x = (col_index * block_size + block_size / 2) as f32;
-                        let y = (row_index * block_size + block_size / 2) as f32;
-                        if let Some(mut sprite) = Sprite::from_char(cell, x, y, &texture_cache) {
-                            sprite.collected = false;
-                            sprites.push(sprite);
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        let mut game_won = false;
-        while !window.window_should_close() && !game_won {
-            framebuffer.clear();
-            let old_x = player.pos.x;
-            let old_y = player.pos.y;
-            process_events(&mut player, &window);
-
-            if is_wall(player.pos.x, old_y, &maze, block_size) { player.pos.x = old_x; }
-            if is_wall(old_x, player.pos.y, &maze, block_size) { player.pos.y = old_y; }
-
-            // Colisión con sprites -> desaparecen al tocarlos
-            for sprite in &mut sprites {
-                if !sprite.collected {
-                    let dx = player.pos.x - sprite.x;
-                    let dy = player.pos.y - sprite.y;
-                    let dist = (dx*dx + dy*dy).sqrt();
-                    if dist < 80.0 {
-                        sprite.collected = true;
-                    }
-                }
-            }
-
-            game_won = sprites.iter().all(|s| s.collected);
-            if game_won { break; }
-
-            let z_buffer = render_world(framebuffer, &maze, block_size, &player, texture_cache);
-
-            let mut sprites_sorted: Vec<_> = sprites.iter().filter(|s| !s.collected).cloned().collect();
-            sprites_sorted.sort_by(|a, b| {
-                let da = (a.x - player.pos.x).powi(2) + (a.y - player.pos.y).powi(2);
-                let db = (b.x - player.pos.x).powi(2) + (b.y - player.pos.y).powi(2);
-                db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
-            });
-            render_sprites(framebuffer, &player, &sprites_sorted, &z_buffer);
-
-            render_maze(framebuffer, &maze, 20, &player, texture_cache);
-
-            for sprite in &sprites {
-                if !sprite.collected {
-                    let x = (sprite.x / 5.0) as u32;
-                    let y = (sprite.y / 5.0) as u32;
-                    framebuffer.set_current_color(Color::YELLOW);
-                    framebuffer.set_pixel(x, y);
-                }
-            }
-
-            framebuffer.swap_buffers(window, raylib_thread);
-            thread::sleep(Duration::from_millis(16));
-        }
-
-        game_won
-    }
-
-    'menu: loop {
-        let mut selected_level = 1;
-
-        while !window.window_should_close() {
-            let mut d = window.begin_drawing(&raylib_thread);
-            d.clear_background(Color::BLACK);
-            d.draw_texture_pro(
-                &start_screen,
-                Rectangle { x:0.0, y:0.0, width:start_screen.width() as f32, height:start_screen.height() as f32 },
-                Rectangle { x:0.0, y:0.0, width:window_width as f32, height:window_height as f32 },
-                Vector2::new(0.0,0.0),
-                0.0,
-                Color::WHITE
-            );
-
-            d.draw_text("Selecciona un nivel con ↑ y ↓, ENTER para empezar", 260, 680, 26, Color::WHITE);
-            let color1 = if selected_level == 1 { Color::YELLOW } else { Color::LIGHTGRAY };
-            let color2 = if selected_level == 2 { Color::YELLOW } else { Color::LIGHTGRAY };
-            d.draw_text("Nivel 1", 600, 730, 36, color1);
-            d.draw_text("Nivel 2", 600, 780, 36, color2);
-
-            if d.is_key_pressed(KeyboardKey::KEY_DOWN) { selected_level = 2; }
-            if d.is_key_pressed(KeyboardKey::KEY_UP) { selected_level = 1; }
-            if d.is_key_pressed(KeyboardKey::KEY_ENTER) { break; }
-            if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) { break 'menu; }
-        }
-
-        if window.window_should_close() { break; }
-
-        let mut all_game_won = false;
-
-        if selected_level == 1 {
-            let level1_won = run_level(&mut window, &raylib_thread, &mut framebuffer, &texture_cache, block_size, "maze.txt");
-            if level1_won {
-                let level2_won = run_level(&mut window, &raylib_thread, &mut framebuffer, &texture_cache, block_size, "maze2.txt");
-                if level2_won { all_game_won = true; }
-            }
-        } else {
-            let _ = run_level(&mut window, &raylib_thread, &mut framebuffer, &texture_cache, block_size, "maze2.txt");
-        }
-
-        if all_game_won {
-            while !window.window_should_close() {
-                let mut d = window.begin_drawing(&raylib_thread);
-                d.clear_background(Color::BLACK);
-                d.draw_texture_pro(
-                    &end_screen,
-                    Rectangle { x:0.0, y:0.0, width:end_screen.width() as f32, height:end_screen.height() as f32 },
-                    Rectangle { x:0.0, y:0.0, width:window_width as f32, height:window_height as f32 },
-                    Vector2::new(0.0,0.0),
-                    0.0,
-                    Color::WHITE
-                );
-                d.draw_text("¡Felicidades! Has completado los dos niveles.", 350, 800, 28, Color::WHITE);
-
-                if d.is_key_pressed(KeyboardKey::KEY_ENTER) || d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
-                    break;
-                }
-            }
-        }
-
-        if window.window_should_close() { break; }
-    }
+    let mut game = Game::new(&mut window, &raylib_thread, settings, audio, framebuffer, texture_cache, endless_seed);
+    game.run(&mut window, &raylib_thread);
 }
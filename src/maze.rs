@@ -1,27 +1,462 @@
 // maze.rs
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+mod header;
+mod json;
+mod tiled;
+mod xml;
 
-pub type Maze = Vec<Vec<char>>;
+use raylib::prelude::*;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
-pub fn load_maze(filename: &str) -> Maze {
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
+use crate::cell::{Cell, Legend, DEFAULT_SPAWN};
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+pub type Maze = Vec<Vec<Cell>>;
+
+pub struct SpawnPoint {
+    pub name: String,
+    pub col: usize,
+    pub row: usize,
+    pub facing: Option<f32>,
+}
+
+pub struct ExitPoint {
+    pub col: usize,
+    pub row: usize,
+    pub target_spawn: Option<String>, // spawn del siguiente nivel; None usa el de inicio
+}
+
+pub struct EnemySpawn {
+    pub kind: char,
+    pub col: usize,
+    pub row: usize,
+    pub patrol: Vec<(usize, usize)>, // celdas (columna, fila) que recorre en orden
+}
+
+// Datos de la cabecera del nivel; los archivos sin cabecera usan los valores por defecto
+pub struct LevelMeta {
+    pub name: String,
+    pub music: Vec<String>,
+    pub sky: Color,
+    pub floor: Color,
+    pub sky_texture: Option<String>,
+    pub floor_texture: Option<String>,
+    pub fog: f32, // densidad de la niebla; 0 la desactiva
+    pub fog_color: Color,
+    pub par_time: Option<f32>, // segundos
+    pub next: Option<String>,
+    pub textures: Vec<(char, String)>, // texturas de paredes y sprites propias del nivel
+}
+
+impl LevelMeta {
+    fn new(name: &str) -> Self {
+        LevelMeta {
+            name: name.to_string(),
+            music: Vec::new(),
+            sky: Color::SKYBLUE,
+            floor: Color::LIGHTGREEN,
+            sky_texture: None,
+            floor_texture: None,
+            fog: 0.0,
+            fog_color: Color::SKYBLUE,
+            par_time: None,
+            next: None,
+            textures: Vec::new(),
+        }
+    }
+
+    // Mezcla el color con el de la niebla según la distancia (niebla exponencial)
+    pub fn fogged(&self, color: Color, distance: f32) -> Color {
+        if self.fog <= 0.0 {
+            return color;
+        }
+        let t = 1.0 - (-self.fog * distance).exp();
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+        Color::new(
+            mix(color.r, self.fog_color.r),
+            mix(color.g, self.fog_color.g),
+            mix(color.b, self.fog_color.b),
+            color.a,
+        )
+    }
+}
+
+// Luz puntual importada de Tiled; posición y radio en celdas
+pub struct Light {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+// Zona rectangular (en celdas) que dispara una acción al entrar
+pub struct Trigger {
+    pub name: String,
+    pub action: String,
+    pub target: Option<String>,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub struct Level {
+    pub maze: Maze,
+    pub width: usize,
+    pub height: usize,
+    pub spawns: Vec<SpawnPoint>,
+    pub exits: Vec<ExitPoint>,
+    pub enemies: Vec<EnemySpawn>,
+    pub meta: LevelMeta,
+    // Texturas de piso y techo por celda (solo en mapas de Tiled); vacías si no hay
+    pub floor_tiles: Vec<Vec<Option<char>>>,
+    pub ceiling_tiles: Vec<Vec<Option<char>>>,
+    pub lights: Vec<Light>,
+    pub triggers: Vec<Trigger>,
+}
+
+impl Level {
+    // Busca el spawn por nombre; si no existe (o no se pide ninguno)
+    // se usa el de inicio, y si tampoco hay, el primero del mapa
+    pub fn spawn(&self, name: Option<&str>) -> &SpawnPoint {
+        name.and_then(|name| self.spawns.iter().find(|spawn| spawn.name == name))
+            .or_else(|| self.spawns.iter().find(|spawn| spawn.name == DEFAULT_SPAWN))
+            .unwrap_or(&self.spawns[0])
+    }
+
+    pub fn exit_at(&self, col: usize, row: usize) -> Option<&ExitPoint> {
+        self.exits.iter().find(|exit| exit.col == col && exit.row == row)
+    }
+
+    pub fn floor_tile(&self, col: usize, row: usize) -> Option<char> {
+        self.floor_tiles.get(row).and_then(|tiles| tiles.get(col)).copied().flatten()
+    }
+
+    pub fn ceiling_tile(&self, col: usize, row: usize) -> Option<char> {
+        self.ceiling_tiles.get(row).and_then(|tiles| tiles.get(col)).copied().flatten()
+    }
+
+    pub fn trigger_at(&self, x: f32, y: f32) -> Option<&Trigger> {
+        self.triggers.iter().find(|t| x >= t.x && x < t.x + t.width && y >= t.y && y < t.y + t.height)
+    }
+
+    // Suma el aporte de las luces cercanas al punto (x, y), en celdas
+    pub fn lit(&self, color: Color, x: f32, y: f32) -> Color {
+        let (mut r, mut g, mut b) = (color.r as f32, color.g as f32, color.b as f32);
+        for light in &self.lights {
+            let distance = ((light.x - x).powi(2) + (light.y - y).powi(2)).sqrt();
+            if distance < light.radius {
+                let strength = 0.5 * (1.0 - distance / light.radius);
+                r += light.color.r as f32 * strength;
+                g += light.color.g as f32 * strength;
+                b += light.color.b as f32 * strength;
+            }
+        }
+        Color::new(r.min(255.0) as u8, g.min(255.0) as u8, b.min(255.0) as u8, color.a)
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io { path: String, source: std::io::Error },
+    Header { line: usize, message: String },
+    Empty,
+    UnknownSymbol { line: usize, column: usize, symbol: char },
+    OpenBorder { line: usize, column: usize },
+    NoSpawn,
+    DuplicateSpawn { line: usize, column: usize, name: String },
+    Tiled { message: String },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io { path, source } => write!(f, "no se pudo leer {}: {}", path, source),
+            LevelError::Header { line, message } => write!(f, "línea {}: {}", line, message),
+            LevelError::Empty => write!(f, "el nivel no tiene ninguna fila"),
+            LevelError::UnknownSymbol { line, column, symbol } => {
+                write!(f, "línea {}, columna {}: símbolo desconocido '{}'", line, column, symbol)
+            }
+            LevelError::OpenBorder { line, column } => {
+                write!(f, "línea {}, columna {}: el borde del laberinto está abierto", line, column)
+            }
+            LevelError::NoSpawn => write!(f, "el nivel no tiene punto de aparición del jugador"),
+            LevelError::DuplicateSpawn { line, column, name } => {
+                write!(f, "línea {}, columna {}: el spawn '{}' ya está definido", line, column, name)
+            }
+            LevelError::Tiled { message } => write!(f, "mapa de Tiled: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+pub fn load_maze(filename: &str) -> Result<Level, LevelError> {
+    let text = fs::read_to_string(filename).map_err(|source| LevelError::Io {
+        path: filename.to_string(),
+        source,
+    })?;
+    let path = Path::new(filename);
+    let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(filename);
+
+    // Los mapas del editor Tiled se reconocen por la extensión
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("tmx") => tiled::parse_tmx(&text, path, name),
+        Some("json") | Some("tmj") => tiled::parse_json(&text, path, name),
+        _ => parse_level(&text, name),
+    }
+}
+
+// Acepta tanto el formato con cabecera como las grillas ASCII de siempre,
+// que se leen con la leyenda clásica y los valores por defecto
+pub fn parse_level(text: &str, name: &str) -> Result<Level, LevelError> {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim_end_matches('\r')).collect();
+    let mut meta = LevelMeta::new(name);
+    let mut legend = Legend::classic();
+
+    let grid_offset = match header::grid_start(&lines) {
+        Some(start) => {
+            header::parse_header(&lines[..start], &mut meta, &mut legend)?;
+            start + 1
+        }
+        None => 0,
+    };
+
+    let mut level = parse_grid(&lines[grid_offset..], grid_offset, &legend)?;
+    level.meta = meta;
+    Ok(level)
+}
+
+// `line_offset` es la cantidad de líneas del archivo antes de la grilla,
+// para que los errores apunten a la línea real
+fn parse_grid(lines: &[&str], line_offset: usize, legend: &Legend) -> Result<Level, LevelError> {
+    let mut lines = lines.to_vec();
+
+    // Las líneas vacías al final del archivo no forman parte del laberinto
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return Err(LevelError::Empty);
+    }
+
+    let mut maze: Maze = Vec::with_capacity(lines.len());
+    let mut spawns: Vec<SpawnPoint> = Vec::new();
+    let mut exits = Vec::new();
+    for (row_index, line) in lines.iter().enumerate() {
+        let mut row = Vec::with_capacity(line.len());
+        for (col_index, symbol) in line.chars().enumerate() {
+            let cell = legend.get(symbol).ok_or(LevelError::UnknownSymbol {
+                line: line_offset + row_index + 1,
+                column: col_index + 1,
+                symbol,
+            })?;
+
+            match cell {
+                Cell::Spawn { facing } => {
+                    let name = legend.name(symbol).unwrap_or(DEFAULT_SPAWN).to_string();
+                    if spawns.iter().any(|spawn| spawn.name == name) {
+                        return Err(LevelError::DuplicateSpawn {
+                            line: line_offset + row_index + 1,
+                            column: col_index + 1,
+                            name,
+                        });
+                    }
+                    spawns.push(SpawnPoint { name, col: col_index, row: row_index, facing });
+                }
+                Cell::Exit => exits.push(ExitPoint {
+                    col: col_index,
+                    row: row_index,
+                    target_spawn: legend.name(symbol).map(str::to_string),
+                }),
+                _ => {}
+            }
+
+            row.push(cell);
+        }
+        maze.push(row);
+    }
+
+    let mut level = finish_level(maze, spawns, exits, line_offset)?;
+    // En los niveles de texto cada enemigo patrulla el pasillo recto más
+    // largo que pasa por su celda
+    for (row, cells) in level.maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if let Cell::Enemy { kind } = cell {
+                let patrol = corridor_patrol(&level.maze, col, row);
+                level.enemies.push(EnemySpawn { kind, col, row, patrol });
+            }
+        }
+    }
+    Ok(level)
+}
+
+// Extremos del pasillo horizontal o vertical (el más largo) que pasa por la celda
+fn corridor_patrol(maze: &Maze, col: usize, row: usize) -> Vec<(usize, usize)> {
+    let open = |c: usize, r: usize| maze.get(r).and_then(|cells| cells.get(c)).is_some_and(|cell| !cell.is_solid());
+    let reach = |dc: isize, dr: isize| {
+        let (mut c, mut r) = (col, row);
+        while open((c as isize + dc) as usize, (r as isize + dr) as usize) {
+            c = (c as isize + dc) as usize;
+            r = (r as isize + dr) as usize;
+        }
+        (c, r)
+    };
+
+    let (left, right) = (reach(-1, 0), reach(1, 0));
+    let (up, down) = (reach(0, -1), reach(0, 1));
+    let (a, b) = if right.0 - left.0 >= down.1 - up.1 { (left, right) } else { (up, down) };
+    if a == b { vec![a] } else { vec![a, b] }
+}
+
+// Validación común a todos los formatos: rellena filas cortas, exige borde
+// cerrado y al menos un spawn
+fn finish_level(
+    mut maze: Maze,
+    spawns: Vec<SpawnPoint>,
+    exits: Vec<ExitPoint>,
+    line_offset: usize,
+) -> Result<Level, LevelError> {
+    if maze.is_empty() {
+        return Err(LevelError::Empty);
+    }
+
+    // Filas más cortas se rellenan con pasillo; si eso abre el borde,
+    // el chequeo de abajo lo reporta
+    let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in &mut maze {
+        row.resize(width, Cell::Empty);
+    }
+    let height = maze.len();
+
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            let on_border = row_index == 0 || row_index == height - 1 || col_index == 0 || col_index == width - 1;
+            if on_border && !cell.is_solid() {
+                return Err(LevelError::OpenBorder { line: line_offset + row_index + 1, column: col_index + 1 });
+            }
+        }
+    }
+
+    if spawns.is_empty() {
+        return Err(LevelError::NoSpawn);
+    }
+
+    Ok(Level {
+        maze,
+        width,
+        height,
+        spawns,
+        exits,
+        enemies: Vec::new(),
+        meta: LevelMeta::new(""),
+        floor_tiles: Vec::new(),
+        ceiling_tiles: Vec::new(),
+        lights: Vec::new(),
+        triggers: Vec::new(),
+    })
 }
 
 pub fn is_wall(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
+    if x < 0.0 || y < 0.0 {
+        return true;
+    }
     let i = (x as usize) / block_size;
     let j = (y as usize) / block_size;
 
-    if j >= maze.len() || i >= maze[0].len() {
-        return true; // fuera del mapa se considera pared
+    match maze.get(j).and_then(|row| row.get(i)) {
+        Some(cell) => cell.is_solid(),
+        None => true, // fuera del mapa se considera pared
+    }
+}
+
+// Grilla para las pruebas: `#` es pared, `D` puerta y cualquier otra cosa pasillo
+#[cfg(test)]
+pub fn grid(rows: &[&str]) -> Maze {
+    rows.iter()
+        .map(|row| {
+            row.chars()
+                .map(|c| match c {
+                    '#' => Cell::Wall { texture: '+' },
+                    'D' => Cell::Door,
+                    _ => Cell::Empty,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> Result<Level, LevelError> {
+        parse_level(&lines.join("\n"), "prueba")
+    }
+
+    fn header_error(lines: &[&str]) -> (usize, String) {
+        match parse(lines) {
+            Err(LevelError::Header { line, message }) => (line, message),
+            Err(other) => panic!("se esperaba un error de cabecera: {}", other),
+            Ok(_) => panic!("se esperaba un error de cabecera"),
+        }
     }
 
-    maze[j][i] != ' ' // cualquier cosa que no sea espacio es pared
+    #[test]
+    fn header_errors_point_at_their_line() {
+        let (line, message) = header_error(&["name = \"x\"", "", "[colores]", "cielo = \"#000000\"", "[grid]", "+++", "+S+", "+++"]);
+        assert_eq!(line, 4);
+        assert!(message.contains("sección desconocida [colores]"), "{}", message);
+
+        let (line, message) = header_error(&["# comentario", "name = \"x\"", "color = \"#ffffff\"", "[grid]", "+++", "+S+", "+++"]);
+        assert_eq!(line, 3);
+        assert!(message.contains("color"), "{}", message);
+
+        // Un campo conocido con el tipo equivocado
+        let (line, message) = header_error(&["fog = \"mucha\"", "[grid]", "+++", "+S+", "+++"]);
+        assert_eq!(line, 1);
+        assert!(message.contains("fog"), "{}", message);
+        let (line, _) = header_error(&["name = \"x\"", "music = 3", "[grid]", "+++", "+S+", "+++"]);
+        assert_eq!(line, 2);
+    }
+
+    #[test]
+    fn quoted_hash_is_a_legend_key_not_a_comment() {
+        let level = parse(&["[legend]", "\"#\" = \"wall -\" # paredes", "[grid]", "###", "#S#", "###"]).unwrap();
+        assert_eq!(level.maze[0][0], Cell::Wall { texture: '-' });
+        assert_eq!(level.spawn(None).col, 1);
+    }
+
+    #[test]
+    fn music_is_a_track_or_a_list() {
+        let grid = ["[grid]", "+++", "+S+", "+++"];
+        let single = parse(&[&["music = \"sounds/a.mp3\""], &grid[..]].concat()).unwrap();
+        assert_eq!(single.meta.music, vec!["sounds/a.mp3"]);
+        let list = parse(&[&["music = [\"sounds/a.mp3\", \"sounds/b.mp3\"]"], &grid[..]].concat()).unwrap();
+        assert_eq!(list.meta.music, vec!["sounds/a.mp3", "sounds/b.mp3"]);
+    }
+
+    #[test]
+    fn short_rows_are_padded_and_open_the_border() {
+        // La última fila se rellena con pasillo, que abre el borde
+        let error = parse(&["name = \"x\"", "[grid]", "+++++", "+S  +", "+++"]).err().unwrap();
+        assert!(matches!(error, LevelError::OpenBorder { line: 5, column: 4 }), "{}", error);
+    }
+
+    #[test]
+    fn rejects_a_duplicate_spawn() {
+        let error = parse(&["+++++", "+S  +", "+ 1S+", "+++++"]).err().unwrap();
+        assert!(matches!(&error, LevelError::DuplicateSpawn { line: 3, column: 4, name } if name == "inicio"), "{}", error);
+        // Cada dígito es un spawn distinto
+        assert_eq!(parse(&["+++++", "+S12+", "+++++"]).unwrap().spawns.len(), 3);
+    }
+
+    #[test]
+    fn unknown_symbols_count_the_header_lines() {
+        let error = parse(&["name = \"x\"", "", "[grid]", "+++++", "+S ?+", "+++++"]).err().unwrap();
+        assert!(matches!(error, LevelError::UnknownSymbol { line: 5, column: 4, symbol: '?' }), "{}", error);
+        let error = parse(&["+++++", "+S ?+", "+++++"]).err().unwrap();
+        assert!(matches!(error, LevelError::UnknownSymbol { line: 2, column: 4, symbol: '?' }), "{}", error);
+    }
 }
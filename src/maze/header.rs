@@ -0,0 +1,193 @@
+// header.rs
+//
+// Cabecera de los archivos de nivel: un subconjunto de TOML con pares
+// `clave = valor`, secciones `[nombre]` y comentarios con `#`. La sección
+// `[grid]` marca el inicio del laberinto y todo lo que sigue es la grilla.
+
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::cell::{Cell, HazardKind, Legend};
+use super::{LevelError, LevelMeta};
+
+pub(super) enum Value {
+    Str(String),
+    Num(f32),
+    List(Vec<String>),
+}
+
+// Devuelve el número de línea donde empieza `[grid]`, si existe
+pub fn grid_start(lines: &[&str]) -> Option<usize> {
+    lines.iter().position(|line| line.trim() == "[grid]")
+}
+
+pub fn parse_header(lines: &[&str], meta: &mut LevelMeta, legend: &mut Legend) -> Result<(), LevelError> {
+    let mut section = String::new();
+
+    for (index, raw) in lines.iter().enumerate() {
+        let line_number = index + 1;
+        let err = |message: String| LevelError::Header { line: line_number, message };
+
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        let (key, value) = split_assignment(line).ok_or_else(|| err("se esperaba `clave = valor`".to_string()))?;
+        let key = unquote(key).unwrap_or(key);
+        let value = parse_value(value).map_err(err)?;
+
+        match section.as_str() {
+            "" => apply_field(meta, key, value).map_err(err)?,
+            "legend" => {
+                let symbol = single_char(key).map_err(err)?;
+                let Value::Str(entry) = value else { return Err(err("la leyenda espera un texto".to_string())) };
+                let (cell, name) = parse_legend_entry(symbol, &entry).map_err(err)?;
+                legend.insert(symbol, cell, name);
+            }
+            "textures" | "sprites" => {
+                let symbol = single_char(key).map_err(err)?;
+                let Value::Str(path) = value else { return Err(err("se esperaba la ruta de la imagen".to_string())) };
+                if section == "sprites" {
+                    legend.insert(symbol, Cell::Pickup { kind: symbol }, None);
+                }
+                meta.textures.push((symbol, path));
+            }
+            other => return Err(err(format!("sección desconocida [{}]", other))),
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn apply_field(meta: &mut LevelMeta, key: &str, value: Value) -> Result<(), String> {
+    match (key, value) {
+        ("name", Value::Str(name)) => meta.name = name,
+        ("music", Value::Str(track)) => meta.music = vec![track],
+        ("music", Value::List(tracks)) => meta.music = tracks,
+        ("sky", Value::Str(color)) => meta.sky = parse_color(&color)?,
+        ("floor", Value::Str(color)) => meta.floor = parse_color(&color)?,
+        ("fog_color", Value::Str(color)) => meta.fog_color = parse_color(&color)?,
+        ("sky_texture", Value::Str(path)) => meta.sky_texture = Some(path),
+        ("floor_texture", Value::Str(path)) => meta.floor_texture = Some(path),
+        ("fog", Value::Num(density)) => meta.fog = density.max(0.0),
+        ("par_time", Value::Num(seconds)) => meta.par_time = Some(seconds),
+        ("next", Value::Str(path)) => meta.next = Some(path),
+        (key, _) => return Err(format!("campo desconocido o con tipo incorrecto: {}", key)),
+    }
+    Ok(())
+}
+
+// Formato: `wall [textura]`, `door`, `empty`, `spawn [nombre] [dirección]`,
+// `exit [spawn destino]`, `pickup [sprite]`, `enemy [sprite]` o `hazard lava|spikes`
+fn parse_legend_entry(symbol: char, entry: &str) -> Result<(Cell, Option<String>), String> {
+    let mut words = entry.split_whitespace();
+    let kind = words.next().unwrap_or("");
+    let first = words.next();
+    let second = words.next();
+
+    let cell = match kind {
+        "empty" => Cell::Empty,
+        "door" => Cell::Door,
+        "exit" => return Ok((Cell::Exit, first.map(str::to_string))),
+        "wall" => Cell::Wall { texture: match first { Some(t) => single_char(t)?, None => symbol } },
+        "pickup" => Cell::Pickup { kind: match first { Some(k) => single_char(k)?, None => symbol } },
+        "enemy" => Cell::Enemy { kind: match first { Some(k) => single_char(k)?, None => symbol } },
+        "hazard" => {
+            let name = first.unwrap_or("");
+            Cell::Hazard { kind: HazardKind::from_name(name).ok_or_else(|| format!("peligro desconocido: {}", name))? }
+        }
+        "spawn" => {
+            let facing = match second {
+                Some(direction) => Some(parse_direction(direction)?),
+                None => None,
+            };
+            return Ok((Cell::Spawn { facing }, first.map(str::to_string)));
+        }
+        other => return Err(format!("tipo de celda desconocido: {}", other)),
+    };
+    Ok((cell, None))
+}
+
+pub(super) fn parse_direction(direction: &str) -> Result<f32, String> {
+    match direction {
+        "este" | "east" => Ok(0.0),
+        "sur" | "south" => Ok(PI / 2.0),
+        "oeste" | "west" => Ok(PI),
+        "norte" | "north" => Ok(-PI / 2.0),
+        other => Err(format!("dirección desconocida: {}", other)),
+    }
+}
+
+pub(super) fn parse_color(text: &str) -> Result<Color, String> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| format!("color inválido: {}", text))
+    };
+    match hex.len() {
+        6 => Ok(Color::new(channel(0)?, channel(2)?, channel(4)?, 255)),
+        8 => Ok(Color::new(channel(0)?, channel(2)?, channel(4)?, channel(6)?)),
+        _ => Err(format!("color inválido: {}", text)),
+    }
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    if let Some(s) = unquote(text) {
+        return Ok(Value::Str(s.to_string()));
+    }
+    if let Some(inner) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let mut items = Vec::new();
+        for item in inner.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let s = unquote(item).ok_or_else(|| format!("la lista solo admite textos: {}", item))?;
+            items.push(s.to_string());
+        }
+        return Ok(Value::List(items));
+    }
+    text.parse::<f32>()
+        .map(Value::Num)
+        .map_err(|_| format!("valor inválido: {}", text))
+}
+
+fn unquote(text: &str) -> Option<&str> {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"'))
+}
+
+fn single_char(text: &str) -> Result<char, String> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("se esperaba un solo símbolo: {}", text)),
+    }
+}
+
+// Separa en el primer `=` que no esté dentro de comillas
+fn split_assignment(line: &str) -> Option<(&str, &str)> {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '=' if !in_quotes => return Some((line[..i].trim(), line[i + 1..].trim())),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
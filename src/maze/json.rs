@@ -0,0 +1,290 @@
+// json.rs
+//
+// Lector de JSON mínimo, lo justo para los mapas de Tiled.
+
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    // Representación en texto de valores simples, para las propiedades de Tiled
+    pub fn to_text(&self) -> String {
+        match self {
+            Json::Null => String::new(),
+            Json::Bool(b) => b.to_string(),
+            Json::Num(n) => n.to_string(),
+            Json::Str(s) => s.clone(),
+            Json::Array(_) | Json::Object(_) => String::new(),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<Json, String> {
+    let mut parser = Parser { chars: text.char_indices().peekable(), text };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.peek() {
+        None => Ok(value),
+        Some(&(pos, _)) => Err(parser.error_at(pos, "contenido extra después del documento")),
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    text: &'a str,
+}
+
+impl Parser<'_> {
+    fn error_at(&self, pos: usize, message: &str) -> String {
+        let line = self.text[..pos].matches('\n').count() + 1;
+        format!("JSON, línea {}: {}", line, message)
+    }
+
+    fn error_here(&mut self, message: &str) -> String {
+        let pos = self.chars.peek().map(|&(pos, _)| pos).unwrap_or(self.text.len());
+        self.error_at(pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            _ => Err(self.error_here(&format!("se esperaba '{}'", expected))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.peek().map(|&(_, c)| c) {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Json::Str),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error_here("valor inesperado")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek().is_some_and(|&(_, c)| c == '}') {
+            self.chars.next();
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, '}')) => return Ok(Json::Object(fields)),
+                _ => return Err(self.error_here("se esperaba ',' o '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek().is_some_and(|&(_, c)| c == ']') {
+            self.chars.next();
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, ']')) => return Ok(Json::Array(items)),
+                _ => return Err(self.error_here("se esperaba ',' o ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(out),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, 'n')) => out.push('\n'),
+                    Some((_, 't')) => out.push('\t'),
+                    Some((_, 'r')) => out.push('\r'),
+                    Some((_, 'b')) => out.push('\u{8}'),
+                    Some((_, 'f')) => out.push('\u{c}'),
+                    Some((_, 'u')) => out.push(self.unicode_escape()?),
+                    Some((_, c)) => out.push(c),
+                    None => return Err(self.error_here("texto sin cerrar")),
+                },
+                Some((_, c)) => out.push(c),
+                None => return Err(self.error_here("texto sin cerrar")),
+            }
+        }
+    }
+
+    // Lo que sigue a `\u`; los caracteres fuera del plano básico vienen como
+    // un par de sustitutos (`\ud83d\ude00`) que se juntan en uno solo
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or('\u{fffd}'));
+        }
+        let mut ahead = self.chars.clone();
+        if ahead.next().map(|(_, c)| c) != Some('\\') || ahead.next().map(|(_, c)| c) != Some('u') {
+            return Ok('\u{fffd}');
+        }
+        self.chars.next();
+        self.chars.next();
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error_here("par de sustitutos \\u inválido"));
+        }
+        Ok(char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap_or('\u{fffd}'))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex: String = (0..4).filter_map(|_| self.chars.next().map(|(_, c)| c)).collect();
+        if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.error_here("escape \\u inválido"));
+        }
+        u32::from_str_radix(&hex, 16).map_err(|_| self.error_here("escape \\u inválido"))
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let mut literal = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                literal.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        literal.parse().map(Json::Num).map_err(|_| self.error_here("número inválido"))
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            match self.chars.next() {
+                Some((_, c)) if c == expected => {}
+                _ => return Err(self.error_here("palabra clave inválida")),
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(text: &str) -> String {
+        parse(text).unwrap().as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        assert_eq!(string(r#""a\"b\\c\/d\n\t\r\b\f""#), "a\"b\\c/d\n\t\r\u{8}\u{c}");
+        assert_eq!(string(r#""\u00e1\u00F1 ñ""#), "áñ ñ");
+    }
+
+    #[test]
+    fn combines_surrogate_pairs() {
+        assert_eq!(string(r#""\ud83d\ude00""#), "😀");
+        assert_eq!(string(r#""x\uD834\uDD1Ey""#), "x𝄞y");
+        // Un sustituto suelto no es un carácter válido
+        assert_eq!(string(r#""\ud83d!""#), "\u{fffd}!");
+        assert_eq!(string(r#""\ude00""#), "\u{fffd}");
+        assert!(parse(r#""\ud83d\u0041""#).is_err());
+    }
+
+    #[test]
+    fn parses_nested_arrays_and_objects() {
+        let value = parse(r#" { "a": [1, [2.5, -3e2], []], "b": {"c": null, "d": true}, "e": {} } "#).unwrap();
+        let a = value.get("a").unwrap().as_array();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[0].as_f64(), Some(1.0));
+        let inner = a[1].as_array();
+        assert_eq!(inner[0].as_f64(), Some(2.5));
+        assert_eq!(inner[1].as_f64(), Some(-300.0));
+        assert!(a[2].as_array().is_empty());
+        let b = value.get("b").unwrap();
+        assert!(matches!(b.get("c"), Some(Json::Null)));
+        assert_eq!(b.get("d").map(Json::to_text).as_deref(), Some("true"));
+        assert!(matches!(value.get("e"), Some(Json::Object(fields)) if fields.is_empty()));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in [
+            "",
+            "{",
+            "[1, 2",
+            "[1 2]",
+            r#"{"a" 1}"#,
+            r#"{"a": 1,}"#,
+            r#""sin cerrar"#,
+            r#""\u12""#,
+            r#""\u12G4""#,
+            "tru",
+            "nul",
+            "1.2.3",
+            "[] []",
+        ] {
+            assert!(parse(text).is_err(), "se aceptó {:?}", text);
+        }
+    }
+
+    #[test]
+    fn reports_the_line_of_the_error() {
+        let error = parse("{\n  \"a\": 1,\n  \"b\": ?\n}").err().unwrap();
+        assert!(error.contains("línea 3"), "{}", error);
+    }
+}
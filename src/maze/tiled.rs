@@ -0,0 +1,845 @@
+// tiled.rs
+//
+// Importa mapas del editor Tiled (.tmx o .json/.tmj). Cada celda del
+// laberinto es un tile del mapa:
+//
+// - capa de tiles `walls`/`paredes`: paredes (o puertas, con la propiedad
+//   de tile `door = true`)
+// - capas de tiles `floor`/`piso` y `ceiling`/`techo`: texturas por celda; un
+//   tile de piso con la propiedad `hazard` (`lava`, `spikes`) hace daño
+// - capa de objetos: `spawn`, `pickup`, `enemy`, `light` y `trigger` según
+//   su clase; un enemigo con polilínea la recorre como patrulla
+//
+// Un tile con la propiedad `texture` usa esa entrada del TextureManager
+// ('+', 'A', ...); si no, se registra su imagen con una clave propia.
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+use crate::cell::{Cell, HazardKind, DEFAULT_SPAWN};
+use super::header::{self, Value};
+use super::json::{self, Json};
+use super::xml::{self, Element};
+use super::{finish_level, EnemySpawn, ExitPoint, Level, LevelError, LevelMeta, Light, Maze, SpawnPoint, Trigger};
+
+// Los 4 bits altos del gid son las banderas de rotación/espejo
+const GID_MASK: u32 = 0x0FFF_FFFF;
+
+// Primer código del área de uso privado de Unicode; las imágenes de los
+// tiles se registran como `TILE_KEY_BASE + gid`
+const TILE_KEY_BASE: u32 = 0xE000;
+const TILE_KEY_LAST: u32 = 0xF8FF;
+
+const DEFAULT_LIGHT_RADIUS: f32 = 3.0;
+
+struct Property {
+    name: String,
+    kind: String,
+    value: String,
+}
+
+struct Tile {
+    id: u32,
+    image: Option<String>,
+    properties: Vec<Property>,
+}
+
+struct Tileset {
+    first_gid: u32,
+    tiles: Vec<Tile>,
+    // Los tilesets de una sola imagen (atlas) no se pueden usar como textura
+    atlas: bool,
+}
+
+struct MapObject {
+    name: String,
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    gid: Option<u32>,
+    points: Vec<(f32, f32)>, // vértices de la polilínea/polígono, absolutos
+    properties: Vec<Property>,
+}
+
+enum Layer {
+    Tiles { name: String, data: Vec<u32> },
+    Objects(Vec<MapObject>),
+}
+
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_width: f32,
+    tile_height: f32,
+    properties: Vec<Property>,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+}
+
+fn error(message: impl Into<String>) -> LevelError {
+    LevelError::Tiled { message: message.into() }
+}
+
+fn find_property<'a>(properties: &'a [Property], name: &str) -> Option<&'a str> {
+    properties.iter().find(|p| p.name == name).map(|p| p.value.as_str())
+}
+
+// Las rutas de Tiled son relativas al archivo que las declara
+fn resolve(dir: &Path, path: &str) -> String {
+    dir.join(path).to_string_lossy().into_owned()
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+pub fn parse_tmx(text: &str, path: &Path, name: &str) -> Result<Level, LevelError> {
+    let root = xml::parse(text).map_err(error)?;
+    if root.name != "map" {
+        return Err(error("se esperaba un elemento <map>"));
+    }
+    let dir = parent_dir(path);
+
+    let number = |key: &str| -> Result<f32, LevelError> {
+        root.attr(key)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| error(format!("falta el atributo {} del mapa", key)))
+    };
+    if root.attr("infinite") == Some("1") {
+        return Err(error("los mapas infinitos no están soportados"));
+    }
+
+    let mut tilesets = Vec::new();
+    for element in root.children_named("tileset") {
+        tilesets.push(tileset_from_tmx(element, &dir)?);
+    }
+    let mut layers = Vec::new();
+    layers_from_tmx(&root, &mut layers)?;
+
+    let map = TiledMap {
+        width: number("width")? as usize,
+        height: number("height")? as usize,
+        tile_width: number("tilewidth")?,
+        tile_height: number("tileheight")?,
+        properties: properties_from_tmx(&root),
+        tilesets,
+        layers,
+    };
+    build_level(map, name)
+}
+
+pub fn parse_json(text: &str, path: &Path, name: &str) -> Result<Level, LevelError> {
+    let root = json::parse(text).map_err(error)?;
+    let dir = parent_dir(path);
+
+    let number = |key: &str| -> Result<f32, LevelError> {
+        root.get(key)
+            .and_then(Json::as_f64)
+            .map(|n| n as f32)
+            .ok_or_else(|| error(format!("falta el campo {} del mapa", key)))
+    };
+    if matches!(root.get("infinite"), Some(Json::Bool(true))) {
+        return Err(error("los mapas infinitos no están soportados"));
+    }
+
+    let mut tilesets = Vec::new();
+    for entry in root.get("tilesets").map(Json::as_array).unwrap_or(&[]) {
+        tilesets.push(tileset_from_json(entry, &dir)?);
+    }
+    let mut layers = Vec::new();
+    layers_from_json(root.get("layers").map(Json::as_array).unwrap_or(&[]), &mut layers)?;
+
+    let map = TiledMap {
+        width: number("width")? as usize,
+        height: number("height")? as usize,
+        tile_width: number("tilewidth")?,
+        tile_height: number("tileheight")?,
+        properties: properties_from_json(&root),
+        tilesets,
+        layers,
+    };
+    build_level(map, name)
+}
+
+// --- TMX ---
+
+fn properties_from_tmx(element: &Element) -> Vec<Property> {
+    let Some(properties) = element.child("properties") else { return Vec::new() };
+    properties
+        .children_named("property")
+        .map(|p| Property {
+            name: p.attr("name").unwrap_or("").to_string(),
+            kind: p.attr("type").unwrap_or("string").to_string(),
+            // Los textos de varias líneas van como contenido del elemento
+            value: p.attr("value").map(str::to_string).unwrap_or_else(|| p.text.clone()),
+        })
+        .collect()
+}
+
+fn tileset_from_tmx(element: &Element, dir: &Path) -> Result<Tileset, LevelError> {
+    let first_gid = element
+        .attr("firstgid")
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| error("tileset sin firstgid"))?;
+
+    match element.attr("source") {
+        Some(source) => load_external_tileset(first_gid, &dir.join(source)),
+        None => tileset_body_from_tmx(first_gid, element, dir),
+    }
+}
+
+fn tileset_body_from_tmx(first_gid: u32, element: &Element, dir: &Path) -> Result<Tileset, LevelError> {
+    let mut tiles = Vec::new();
+    for tile in element.children_named("tile") {
+        let id = tile.attr("id").and_then(|v| v.parse().ok()).ok_or_else(|| error("tile sin id"))?;
+        tiles.push(Tile {
+            id,
+            image: tile.child("image").and_then(|image| image.attr("source")).map(|source| resolve(dir, source)),
+            properties: properties_from_tmx(tile),
+        });
+    }
+    Ok(Tileset { first_gid, tiles, atlas: element.child("image").is_some() })
+}
+
+fn layers_from_tmx(parent: &Element, layers: &mut Vec<Layer>) -> Result<(), LevelError> {
+    for element in &parent.children {
+        match element.name.as_str() {
+            "layer" => {
+                let name = element.attr("name").unwrap_or("").to_string();
+                let data = element.child("data").ok_or_else(|| error(format!("la capa {} no tiene datos", name)))?;
+                layers.push(Layer::Tiles { data: tile_data_from_tmx(data, &name)?, name });
+            }
+            "objectgroup" => {
+                let objects = element.children_named("object").map(object_from_tmx).collect();
+                layers.push(Layer::Objects(objects));
+            }
+            "group" => layers_from_tmx(element, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn tile_data_from_tmx(data: &Element, layer: &str) -> Result<Vec<u32>, LevelError> {
+    if data.child("chunk").is_some() {
+        return Err(error("los mapas infinitos no están soportados"));
+    }
+    match data.attr("encoding") {
+        Some("csv") => data.text
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse().map_err(|_| error(format!("capa {}: gid inválido {}", layer, gid))))
+            .collect(),
+        Some("base64") => decode_gids(data.text.trim(), data.attr("compression"), layer),
+        Some(other) => Err(error(format!("capa {}: codificación {} no soportada", layer, other))),
+        None => Ok(data
+            .children_named("tile")
+            .map(|tile| tile.attr("gid").and_then(|v| v.parse().ok()).unwrap_or(0))
+            .collect()),
+    }
+}
+
+fn object_from_tmx(element: &Element) -> MapObject {
+    let number = |key: &str| element.attr(key).and_then(|v| v.parse().ok()).unwrap_or(0.0);
+    let (x, y) = (number("x"), number("y"));
+    // `points="0,0 64,0 64,32"`, relativos a la posición del objeto
+    let points = element
+        .child("polyline")
+        .or(element.child("polygon"))
+        .and_then(|shape| shape.attr("points"))
+        .map(|points| {
+            points
+                .split_whitespace()
+                .filter_map(|pair| pair.split_once(','))
+                .filter_map(|(px, py)| Some((x + px.parse::<f32>().ok()?, y + py.parse::<f32>().ok()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    MapObject {
+        name: element.attr("name").unwrap_or("").to_string(),
+        // Tiled 1.9 renombró `type` a `class`
+        class: element.attr("class").or(element.attr("type")).unwrap_or("").to_string(),
+        x,
+        y,
+        width: number("width"),
+        height: number("height"),
+        gid: element.attr("gid").and_then(|v| v.parse().ok()),
+        points,
+        properties: properties_from_tmx(element),
+    }
+}
+
+// --- JSON ---
+
+fn properties_from_json(value: &Json) -> Vec<Property> {
+    value
+        .get("properties")
+        .map(Json::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .map(|p| Property {
+            name: p.get("name").and_then(Json::as_str).unwrap_or("").to_string(),
+            kind: p.get("type").and_then(Json::as_str).unwrap_or("string").to_string(),
+            value: p.get("value").map(Json::to_text).unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn tileset_from_json(value: &Json, dir: &Path) -> Result<Tileset, LevelError> {
+    let first_gid = value
+        .get("firstgid")
+        .and_then(Json::as_f64)
+        .ok_or_else(|| error("tileset sin firstgid"))? as u32;
+
+    match value.get("source").and_then(Json::as_str) {
+        Some(source) => load_external_tileset(first_gid, &dir.join(source)),
+        None => tileset_body_from_json(first_gid, value, dir),
+    }
+}
+
+fn tileset_body_from_json(first_gid: u32, value: &Json, dir: &Path) -> Result<Tileset, LevelError> {
+    let mut tiles = Vec::new();
+    for tile in value.get("tiles").map(Json::as_array).unwrap_or(&[]) {
+        let id = tile.get("id").and_then(Json::as_f64).ok_or_else(|| error("tile sin id"))? as u32;
+        tiles.push(Tile {
+            id,
+            image: tile.get("image").and_then(Json::as_str).map(|source| resolve(dir, source)),
+            properties: properties_from_json(tile),
+        });
+    }
+    Ok(Tileset { first_gid, tiles, atlas: value.get("image").is_some() })
+}
+
+fn layers_from_json(values: &[Json], layers: &mut Vec<Layer>) -> Result<(), LevelError> {
+    for value in values {
+        let name = value.get("name").and_then(Json::as_str).unwrap_or("").to_string();
+        match value.get("type").and_then(Json::as_str) {
+            Some("tilelayer") => {
+                if value.get("chunks").is_some() {
+                    return Err(error("los mapas infinitos no están soportados"));
+                }
+                let data = match value.get("data") {
+                    Some(Json::Str(encoded)) => decode_gids(
+                        encoded,
+                        value.get("compression").and_then(Json::as_str).filter(|c| !c.is_empty()),
+                        &name,
+                    )?,
+                    Some(Json::Array(items)) => items.iter().map(|gid| gid.as_f64().unwrap_or(0.0) as u32).collect(),
+                    _ => return Err(error(format!("la capa {} no tiene datos", name))),
+                };
+                layers.push(Layer::Tiles { name, data });
+            }
+            Some("objectgroup") => {
+                let objects = value.get("objects").map(Json::as_array).unwrap_or(&[]);
+                layers.push(Layer::Objects(objects.iter().map(object_from_json).collect()));
+            }
+            Some("group") => layers_from_json(value.get("layers").map(Json::as_array).unwrap_or(&[]), layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn object_from_json(value: &Json) -> MapObject {
+    let number = |key: &str| value.get(key).and_then(Json::as_f64).unwrap_or(0.0) as f32;
+    let text = |key: &str| value.get(key).and_then(Json::as_str).filter(|s| !s.is_empty());
+    let (x, y) = (number("x"), number("y"));
+    let points = value
+        .get("polyline")
+        .or(value.get("polygon"))
+        .map(Json::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|point| {
+            let px = point.get("x").and_then(Json::as_f64)? as f32;
+            let py = point.get("y").and_then(Json::as_f64)? as f32;
+            Some((x + px, y + py))
+        })
+        .collect();
+    MapObject {
+        name: text("name").unwrap_or("").to_string(),
+        class: text("class").or(text("type")).unwrap_or("").to_string(),
+        x,
+        y,
+        width: number("width"),
+        height: number("height"),
+        gid: value.get("gid").and_then(Json::as_f64).map(|gid| gid as u32),
+        points,
+        properties: properties_from_json(value),
+    }
+}
+
+// --- Comunes ---
+
+// Tilesets externos: .tsx (XML) o .tsj/.json
+fn load_external_tileset(first_gid: u32, path: &Path) -> Result<Tileset, LevelError> {
+    let text = fs::read_to_string(path).map_err(|source| LevelError::Io {
+        path: path.to_string_lossy().into_owned(),
+        source,
+    })?;
+    let dir = parent_dir(path);
+
+    if path.extension().is_some_and(|ext| ext == "tsx") {
+        let root = xml::parse(&text).map_err(error)?;
+        tileset_body_from_tmx(first_gid, &root, &dir)
+    } else {
+        let root = json::parse(&text).map_err(error)?;
+        tileset_body_from_json(first_gid, &root, &dir)
+    }
+}
+
+// Capas en base64 sin comprimir: gids de 32 bits little-endian
+fn decode_gids(encoded: &str, compression: Option<&str>, layer: &str) -> Result<Vec<u32>, LevelError> {
+    if let Some(compression) = compression {
+        return Err(error(format!(
+            "capa {}: compresión {} no soportada; guarda el mapa en CSV o base64 sin comprimir",
+            layer, compression
+        )));
+    }
+    let bytes = decode_base64(encoded).ok_or_else(|| error(format!("capa {}: base64 inválido", layer)))?;
+    Ok(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+
+    // El relleno `=` solo puede ir al final, y un carácter suelto en el
+    // último grupo no alcanza para un byte
+    let text: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    let data = text.strip_suffix(b"==").or_else(|| text.strip_suffix(b"=")).unwrap_or(&text);
+    if data.len() % 4 == 1 || (data.len() != text.len() && !text.len().is_multiple_of(4)) {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &c in data {
+        buffer = (buffer << 6) | value(c)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+// Tiled guarda los colores como #AARRGGBB; la cabecera espera #RRGGBBAA
+fn tiled_color(text: &str) -> String {
+    match text.strip_prefix('#') {
+        Some(hex) if hex.len() == 8 => format!("#{}{}", &hex[2..], &hex[..2]),
+        _ => text.to_string(),
+    }
+}
+
+// Las propiedades del mapa son los mismos campos que la cabecera de los
+// niveles de texto (name, music, sky, fog, next, ...)
+fn apply_map_properties(meta: &mut LevelMeta, properties: &[Property]) -> Result<(), LevelError> {
+    for property in properties {
+        let value = match property.kind.as_str() {
+            "int" | "float" => Value::Num(
+                property.value.parse().map_err(|_| error(format!("propiedad {}: número inválido", property.name)))?,
+            ),
+            "color" => Value::Str(tiled_color(&property.value)),
+            // La música puede ser una lista separada por comas
+            _ if property.name == "music" && property.value.contains(',') => {
+                Value::List(property.value.split(',').map(|track| track.trim().to_string()).collect())
+            }
+            _ => Value::Str(property.value.clone()),
+        };
+        header::apply_field(meta, &property.name, value)
+            .map_err(|message| error(format!("propiedad del mapa: {}", message)))?;
+    }
+    Ok(())
+}
+
+struct TileKeys<'a> {
+    tilesets: &'a [Tileset],
+    keys: HashMap<u32, char>,
+}
+
+impl TileKeys<'_> {
+    fn tile(&self, gid: u32) -> Option<&Tile> {
+        let tileset = self.tilesets.iter().rev().find(|tileset| tileset.first_gid <= gid)?;
+        tileset.tiles.iter().find(|tile| tile.id == gid - tileset.first_gid)
+    }
+
+    // Clave del TextureManager para el tile; registra su imagen la primera vez
+    fn key(&mut self, gid: u32, meta: &mut LevelMeta) -> Result<char, LevelError> {
+        if let Some(&key) = self.keys.get(&gid) {
+            return Ok(key);
+        }
+
+        let tile = self.tile(gid);
+        let key = match tile.and_then(|tile| find_property(&tile.properties, "texture")) {
+            Some(texture) => {
+                let mut chars = texture.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err(error(format!("tile {}: `texture` debe ser un solo símbolo", gid))),
+                }
+            }
+            None => {
+                let Some(image) = tile.and_then(|tile| tile.image.clone()) else {
+                    let atlas = self.tilesets.iter().rev().find(|t| t.first_gid <= gid).is_some_and(|t| t.atlas);
+                    return Err(error(if atlas {
+                        format!("tile {}: los tilesets de una sola imagen no están soportados; usa una colección de imágenes", gid)
+                    } else {
+                        format!("tile {}: no tiene imagen ni propiedad `texture`", gid)
+                    }));
+                };
+                let key = TILE_KEY_BASE
+                    .checked_add(gid)
+                    .filter(|&code| code <= TILE_KEY_LAST)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(format!("tile {}: demasiados tiles distintos", gid)))?;
+                meta.textures.push((key, image));
+                key
+            }
+        };
+
+        self.keys.insert(gid, key);
+        Ok(key)
+    }
+
+    fn is_door(&self, gid: u32) -> bool {
+        self.tile(gid).and_then(|tile| find_property(&tile.properties, "door")) == Some("true")
+    }
+
+    fn hazard(&self, gid: u32) -> Result<Option<HazardKind>, LevelError> {
+        match self.tile(gid).and_then(|tile| find_property(&tile.properties, "hazard")) {
+            Some(name) => HazardKind::from_name(name)
+                .map(Some)
+                .ok_or_else(|| error(format!("tile {}: peligro desconocido {}", gid, name))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn build_level(map: TiledMap, name: &str) -> Result<Level, LevelError> {
+    let (width, height) = (map.width, map.height);
+    if width == 0 || height == 0 {
+        return Err(LevelError::Empty);
+    }
+
+    let mut meta = LevelMeta::new(name);
+    apply_map_properties(&mut meta, &map.properties)?;
+
+    let mut keys = TileKeys { tilesets: &map.tilesets, keys: HashMap::new() };
+    let mut maze: Maze = vec![vec![Cell::Empty; width]; height];
+    let mut floor_tiles = Vec::new();
+    let mut ceiling_tiles = Vec::new();
+    let mut spawns: Vec<SpawnPoint> = Vec::new();
+    let mut exits = Vec::new();
+    let mut enemies = Vec::new();
+    let mut lights = Vec::new();
+    let mut triggers = Vec::new();
+
+    for layer in &map.layers {
+        let Layer::Tiles { name, data } = layer else { continue };
+        if data.len() != width * height {
+            return Err(error(format!("la capa {} tiene {} tiles y el mapa {}x{}", name, data.len(), width, height)));
+        }
+
+        match name.to_lowercase().as_str() {
+            "walls" | "paredes" => {
+                for (index, &raw) in data.iter().enumerate() {
+                    let gid = raw & GID_MASK;
+                    if gid == 0 {
+                        continue;
+                    }
+                    maze[index / width][index % width] = if keys.is_door(gid) {
+                        Cell::Door
+                    } else {
+                        Cell::Wall { texture: keys.key(gid, &mut meta)? }
+                    };
+                }
+            }
+            layer_name @ ("floor" | "piso" | "ceiling" | "techo") => {
+                let mut tiles = vec![vec![None; width]; height];
+                let is_floor = matches!(layer_name, "floor" | "piso");
+                for (index, &raw) in data.iter().enumerate() {
+                    let gid = raw & GID_MASK;
+                    if gid == 0 {
+                        continue;
+                    }
+                    let (row, col) = (index / width, index % width);
+                    tiles[row][col] = Some(keys.key(gid, &mut meta)?);
+                    if is_floor
+                        && let Some(kind) = keys.hazard(gid)?
+                        && !maze[row][col].is_solid()
+                    {
+                        maze[row][col] = Cell::Hazard { kind };
+                    }
+                }
+                if is_floor {
+                    floor_tiles = tiles;
+                } else {
+                    ceiling_tiles = tiles;
+                }
+            }
+            // Capas auxiliares del diseñador
+            _ => {}
+        }
+    }
+
+    let objects = map.layers.iter().flat_map(|layer| match layer {
+        Layer::Objects(objects) => objects.as_slice(),
+        Layer::Tiles { .. } => &[],
+    });
+    for object in objects {
+        // Sin clase es el tipo por defecto de Tiled: notas o marcas del
+        // diseñador que el juego no usa
+        if object.class.is_empty() {
+            continue;
+        }
+        // Los objetos con tile se anclan por la esquina inferior izquierda
+        let top = if object.gid.is_some() { object.y - object.height } else { object.y };
+        let center_x = (object.x + object.width / 2.0) / map.tile_width;
+        let center_y = (top + object.height / 2.0) / map.tile_height;
+        let (col, row) = (center_x.floor() as isize, center_y.floor() as isize);
+        if col < 0 || row < 0 || col as usize >= width || row as usize >= height {
+            return Err(error(format!("el objeto '{}' está fuera del mapa", object.name)));
+        }
+        let (col, row) = (col as usize, row as usize);
+        let property = |name: &str| find_property(&object.properties, name);
+
+        match object.class.as_str() {
+            "spawn" => {
+                let name = if object.name.is_empty() { DEFAULT_SPAWN.to_string() } else { object.name.clone() };
+                if spawns.iter().any(|spawn| spawn.name == name) {
+                    return Err(LevelError::DuplicateSpawn { line: row + 1, column: col + 1, name });
+                }
+                // En grados, en sentido horario desde el este como en Tiled
+                let facing = match property("facing") {
+                    Some(degrees) => Some(
+                        degrees.parse::<f32>().map_err(|_| error(format!("spawn {}: `facing` inválido", name)))? * PI / 180.0,
+                    ),
+                    None => None,
+                };
+                maze[row][col] = Cell::Spawn { facing };
+                spawns.push(SpawnPoint { name, col, row, facing });
+            }
+            "pickup" => {
+                let kind = match (property("kind").and_then(|k| k.chars().next()), object.gid) {
+                    (Some(kind), _) => kind,
+                    (None, Some(gid)) => keys.key(gid & GID_MASK, &mut meta)?,
+                    (None, None) => return Err(error(format!("el pickup '{}' no tiene `kind` ni tile", object.name))),
+                };
+                maze[row][col] = Cell::Pickup { kind };
+            }
+            "enemy" => {
+                let kind = match (property("kind").and_then(|k| k.chars().next()), object.gid) {
+                    (Some(kind), _) => kind,
+                    (None, Some(gid)) => keys.key(gid & GID_MASK, &mut meta)?,
+                    (None, None) => 'X',
+                };
+                let mut patrol = Vec::new();
+                for &(px, py) in &object.points {
+                    let (pc, pr) = ((px / map.tile_width).floor(), (py / map.tile_height).floor());
+                    if pc < 0.0 || pr < 0.0 || pc as usize >= width || pr as usize >= height {
+                        return Err(error(format!("la patrulla del enemigo '{}' sale del mapa", object.name)));
+                    }
+                    patrol.push((pc as usize, pr as usize));
+                }
+                // Sin polilínea el enemigo se queda de guardia en su celda
+                if patrol.is_empty() {
+                    patrol.push((col, row));
+                }
+                // Una polilínea empieza en la posición del objeto; el enemigo aparece ahí
+                let (col, row) = patrol[0];
+                maze[row][col] = Cell::Enemy { kind };
+                enemies.push(EnemySpawn { kind, col, row, patrol });
+            }
+            "light" => {
+                let radius = match property("radius") {
+                    Some(radius) => radius.parse().map_err(|_| error(format!("luz '{}': radio inválido", object.name)))?,
+                    None => DEFAULT_LIGHT_RADIUS,
+                };
+                let color = match property("color") {
+                    Some(color) => header::parse_color(&tiled_color(color)).map_err(error)?,
+                    None => Color::WHITE,
+                };
+                lights.push(Light { x: center_x, y: center_y, radius, color });
+            }
+            "trigger" => {
+                let action = property("action")
+                    .ok_or_else(|| error(format!("el trigger '{}' no tiene `action`", object.name)))?;
+                let target = property("target").map(str::to_string);
+                let x = object.x / map.tile_width;
+                let y = top / map.tile_height;
+                let trigger_width = (object.width / map.tile_width).max(1.0);
+                let trigger_height = (object.height / map.tile_height).max(1.0);
+
+                // Las salidas son celdas del laberinto como la `E` de los niveles de texto
+                if action == "exit" {
+                    let cols = (x.floor() as usize)..((x + trigger_width).ceil() as usize).min(width);
+                    let rows = (y.floor() as usize)..((y + trigger_height).ceil() as usize).min(height);
+                    for exit_row in rows {
+                        for exit_col in cols.clone() {
+                            maze[exit_row][exit_col] = Cell::Exit;
+                            exits.push(ExitPoint { col: exit_col, row: exit_row, target_spawn: target.clone() });
+                        }
+                    }
+                } else {
+                    triggers.push(Trigger {
+                        name: object.name.clone(),
+                        action: action.to_string(),
+                        target,
+                        x,
+                        y,
+                        width: trigger_width,
+                        height: trigger_height,
+                    });
+                }
+            }
+            other => return Err(error(format!("el objeto '{}' tiene una clase desconocida: '{}'", object.name, other))),
+        }
+    }
+
+    let mut level = finish_level(maze, spawns, exits, 0)?;
+    level.meta = meta;
+    level.enemies = enemies;
+    level.floor_tiles = floor_tiles;
+    level.ceiling_tiles = ceiling_tiles;
+    level.lights = lights;
+    level.triggers = triggers;
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tileset con una pared (gid 1) y una puerta (gid 2), sin imágenes
+    const TMX_TILESET: &str = r#"<tileset firstgid="1" name="celdas">
+  <tile id="0"><properties><property name="texture" value="+"/></properties></tile>
+  <tile id="1"><properties><property name="door" type="bool" value="true"/></properties></tile>
+ </tileset>"#;
+
+    fn tmx(data: &str, objects: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="3" tilewidth="32" tileheight="32" infinite="0">
+ <properties><property name="name" value="Prueba &amp; &#x41;"/></properties>
+ {}
+ <layer id="1" name="walls" width="3" height="3">{}</layer>
+ <objectgroup id="2" name="objetos">{}</objectgroup>
+</map>"#,
+            TMX_TILESET, data, objects
+        )
+    }
+
+    const SPAWN: &str = r#"<object id="1" name="inicio" type="spawn" x="48" y="48"/>"#;
+
+    fn check_ring(level: &Level) {
+        assert_eq!(level.maze[0][0], Cell::Wall { texture: '+' });
+        assert!(!level.maze[1][1].is_solid());
+        assert_eq!(level.maze[1][2], Cell::Door);
+        assert_eq!(level.spawns.len(), 1);
+        assert_eq!((level.spawns[0].col, level.spawns[0].row), (1, 1));
+        assert_eq!(level.spawns[0].name, "inicio");
+    }
+
+    #[test]
+    fn decodes_base64() {
+        assert_eq!(decode_base64("TWFu").as_deref(), Some(&b"Man"[..]));
+        assert_eq!(decode_base64("TWE=").as_deref(), Some(&b"Ma"[..]));
+        assert_eq!(decode_base64("TQ==").as_deref(), Some(&b"M"[..]));
+        assert_eq!(decode_base64(" TW\n Fu\t").as_deref(), Some(&b"Man"[..]));
+        assert_eq!(decode_base64("TWE").as_deref(), Some(&b"Ma"[..]));
+        assert_eq!(decode_base64("").as_deref(), Some(&b""[..]));
+        assert_eq!(decode_base64("+/+/").as_deref(), Some(&[0xfb, 0xff, 0xbf][..]));
+    }
+
+    #[test]
+    fn rejects_malformed_base64() {
+        assert!(decode_base64("TWF").is_some());
+        assert!(decode_base64("T").is_none());
+        assert!(decode_base64("TQ==TQ==").is_none());
+        assert!(decode_base64("TW=u").is_none());
+        assert!(decode_base64("TWE==").is_none());
+        assert!(decode_base64("TW-u").is_none());
+    }
+
+    #[test]
+    fn decodes_little_endian_gids() {
+        assert_eq!(decode_gids("AQAAAAIAAIA=", None, "walls").unwrap(), vec![1, 0x8000_0002]);
+        assert!(decode_gids("AQAAAA==", Some("zlib"), "walls").is_err());
+        assert!(decode_gids("A", None, "walls").is_err());
+    }
+
+    #[test]
+    fn loads_a_tmx_map_in_csv_and_base64() {
+        let csv = tmx(r#"<data encoding="csv">
+1,1,1,
+1,0,2,
+1,1,1
+</data>"#, SPAWN);
+        let level = parse_tmx(&csv, Path::new("mapa.tmx"), "mapa").unwrap();
+        check_ring(&level);
+        assert_eq!(level.meta.name, "Prueba & A");
+
+        let base64 = tmx(r#"<data encoding="base64">
+   AQAAAAEAAAABAAAAAQAAAAAAAAACAAAAAQAAAAEAAAABAAAA
+  </data>"#, SPAWN);
+        check_ring(&parse_tmx(&base64, Path::new("mapa.tmx"), "mapa").unwrap());
+    }
+
+    #[test]
+    fn loads_a_json_map() {
+        let text = r#"{
+  "width": 3, "height": 3, "tilewidth": 32, "tileheight": 32, "infinite": false,
+  "tilesets": [{ "firstgid": 1, "tiles": [
+    { "id": 0, "properties": [{ "name": "texture", "type": "string", "value": "+" }] },
+    { "id": 1, "properties": [{ "name": "door", "type": "bool", "value": true }] }
+  ]}],
+  "layers": [
+    { "type": "tilelayer", "name": "walls", "width": 3, "height": 3, "data": [1, 1, 1, 1, 0, 2, 1, 1, 1] },
+    { "type": "objectgroup", "name": "objetos", "objects": [
+      { "id": 1, "name": "inicio", "type": "spawn", "x": 48, "y": 48, "width": 0, "height": 0 },
+      { "id": 2, "name": "nota", "type": "", "x": 500, "y": 500, "width": 0, "height": 0 }
+    ]}
+  ]
+}"#;
+        check_ring(&parse_json(text, Path::new("mapa.json"), "mapa").unwrap());
+    }
+
+    #[test]
+    fn ignores_objects_without_class() {
+        let objects = format!(r#"{}<object id="2" name="nota" x="900" y="900"/><object id="3" x="40" y="40" class=""/>"#, SPAWN);
+        let text = tmx(r#"<data encoding="csv">1,1,1,1,0,2,1,1,1</data>"#, &objects);
+        check_ring(&parse_tmx(&text, Path::new("mapa.tmx"), "mapa").unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown_classes_and_bad_layers() {
+        let unknown = format!(r#"{}<object id="2" name="raro" class="dragon" x="40" y="40"/>"#, SPAWN);
+        let text = tmx(r#"<data encoding="csv">1,1,1,1,0,2,1,1,1</data>"#, &unknown);
+        assert!(parse_tmx(&text, Path::new("mapa.tmx"), "mapa").is_err());
+
+        let short = tmx(r#"<data encoding="csv">1,1,1</data>"#, SPAWN);
+        assert!(parse_tmx(&short, Path::new("mapa.tmx"), "mapa").is_err());
+
+        let compressed = tmx(r#"<data encoding="base64" compression="zlib">eJw=</data>"#, SPAWN);
+        assert!(parse_tmx(&compressed, Path::new("mapa.tmx"), "mapa").is_err());
+
+        assert!(parse_tmx("<map width=\"3\"></map>", Path::new("mapa.tmx"), "mapa").is_err());
+        assert!(parse_json("{\"width\": 3", Path::new("mapa.json"), "mapa").is_err());
+    }
+}
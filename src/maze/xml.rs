@@ -0,0 +1,238 @@
+// xml.rs
+//
+// Lector de XML mínimo para los mapas .tmx y tilesets .tsx de Tiled:
+// elementos, atributos, texto y secciones CDATA; ignora declaraciones y
+// comentarios.
+
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+pub fn parse(text: &str) -> Result<Element, String> {
+    let mut pos = 0;
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+
+    while pos < text.len() {
+        let rest = &text[pos..];
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&unescape(&rest[..end]));
+            }
+            pos += end;
+            continue;
+        }
+
+        // La línea se cuenta solo al armar un error; contarla en cada
+        // etiqueta vuelve cuadrático leer capas con un <tile> por celda
+        let tag_start = pos;
+        let err = |message: &str| {
+            let line = text[..tag_start].matches('\n').count() + 1;
+            format!("XML, línea {}: {}", line, message)
+        };
+
+        // El contenido de CDATA va tal cual, sin entidades
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").ok_or_else(|| err("CDATA sin cerrar"))?;
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&cdata[..end]);
+            }
+            pos += "<![CDATA[".len() + end + "]]>".len();
+            continue;
+        }
+
+        if rest.starts_with("<?") || rest.starts_with("<!--") || rest.starts_with("<!") {
+            let terminator = if rest.starts_with("<!--") { "-->" } else { ">" };
+            let end = rest.find(terminator).ok_or_else(|| err("etiqueta sin cerrar"))?;
+            pos += end + terminator.len();
+            continue;
+        }
+
+        let end = tag_end(rest).ok_or_else(|| err("etiqueta sin cerrar"))?;
+        let tag = &rest[1..end];
+        pos += end + 1;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let element = stack.pop().ok_or_else(|| err("cierre sin apertura"))?;
+            if element.name != name.trim() {
+                return Err(err(&format!("se esperaba </{}>", element.name)));
+            }
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let element = Element {
+            name: tag[..name_end].to_string(),
+            attrs: parse_attrs(&tag[name_end..]).map_err(|m| err(&m))?,
+            children: Vec::new(),
+            text: String::new(),
+        };
+
+        if self_closing {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+        } else {
+            stack.push(element);
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("XML: falta cerrar <{}>", open.name));
+    }
+    root.ok_or_else(|| "XML: documento vacío".to_string())
+}
+
+// Posición del `>` que cierra la etiqueta; los que están dentro de un valor
+// entre comillas no cuentan
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_attrs(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut attrs = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or("atributo sin valor")?;
+        let name = rest[..eq].trim().to_string();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'').ok_or("valor de atributo sin comillas")?;
+        let close = after[1..].find(quote).ok_or("valor de atributo sin cerrar")?;
+        attrs.push((name, unescape(&after[1..close + 1])));
+        rest = after[close + 2..].trim_start();
+    }
+    Ok(attrs)
+}
+
+// Entidades con nombre y referencias numéricas (`&#10;`, `&#x41;`); las
+// que no se entienden quedan como están
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "amp" => '&',
+                name => {
+                    let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => name.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_elements_and_attributes() {
+        let root = parse("<?xml version=\"1.0\"?>\n<map width='3' height=\"2\"><!-- capa --><layer name=\"walls\"><data>1,2</data></layer><object/></map>").unwrap();
+        assert_eq!(root.name, "map");
+        assert_eq!(root.attr("width"), Some("3"));
+        assert_eq!(root.attr("height"), Some("2"));
+        assert_eq!(root.child("layer").and_then(|layer| layer.attr("name")), Some("walls"));
+        assert_eq!(root.child("layer").and_then(|layer| layer.child("data")).map(|data| data.text.as_str()), Some("1,2"));
+        assert_eq!(root.children_named("object").count(), 1);
+    }
+
+    #[test]
+    fn quoted_angle_brackets_do_not_end_the_tag() {
+        let root = parse("<property name=\"a>b\" value='x > y'/>").unwrap();
+        assert_eq!(root.attr("name"), Some("a>b"));
+        assert_eq!(root.attr("value"), Some("x > y"));
+    }
+
+    #[test]
+    fn decodes_named_and_numeric_entities() {
+        let root = parse("<p v=\"&lt;&amp;&gt;&quot;&apos;\">a&#10;b&#x41;&#X42;</p>").unwrap();
+        assert_eq!(root.attr("v"), Some("<&>\"'"));
+        assert_eq!(root.text, "a\nbAB");
+    }
+
+    #[test]
+    fn leaves_unknown_entities_alone() {
+        assert_eq!(unescape("a & b &nope; &#xZZ; &"), "a & b &nope; &#xZZ; &");
+        assert_eq!(unescape("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn keeps_cdata_verbatim() {
+        let root = parse("<data><![CDATA[1,2 <3> &amp;]]></data>").unwrap();
+        assert_eq!(root.text, "1,2 <3> &amp;");
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse("<map><layer></map>").is_err());
+        assert!(parse("<map>").is_err());
+        assert!(parse("</map>").is_err());
+        assert!(parse("<map width=3/>").is_err());
+        assert!(parse("<map name=\"sin cerrar/>").is_err());
+        assert!(parse("<data><![CDATA[abierto</data>").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn reports_the_line_of_the_bad_tag() {
+        let error = parse("<map>\n  <layer>\n  </map>\n</layer>").err().unwrap();
+        assert!(error.starts_with("XML, línea 3:"), "{}", error);
+    }
+}
@@ -0,0 +1,281 @@
+// minimap.rs
+//
+// Minimapa en la esquina de la pantalla. Se dibuja píxel a píxel: cada
+// píxel del marco se pasa a un punto del mundo (con el zoom y, si está
+// activado, girando con el jugador) y se pinta la celda que cae ahí. Solo
+// se ven las celdas que el jugador ya vio (niebla de guerra); encima van el
+// camino de la pista, los sprites de las celdas exploradas y el cono de
+// visión.
+
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::cell::Cell;
+use crate::config::Settings;
+use crate::framebuffer::Framebuffer;
+use crate::hint::HINT_COLOR;
+use crate::maze::Maze;
+use crate::player::Player;
+use crate::textures::TextureManager;
+use crate::cell_to_texture_color;
+
+// Píxeles por celda: el zoom va de un mapa amplio a uno de cerca
+const MIN_ZOOM: f32 = 6.0;
+const MAX_ZOOM: f32 = 40.0;
+const DEFAULT_ZOOM: f32 = 16.0;
+const ZOOM_STEP: f32 = 1.25;
+// Lado del minimapa como fracción del alto de la pantalla
+const SIZE: f32 = 0.28;
+const MARGIN: f32 = 10.0;
+// Hasta dónde se exploran celdas por frente al jugador, en celdas
+const EXPLORE_RANGE: f32 = 8.0;
+const EXPLORE_RAYS: usize = 32;
+
+const FOG_COLOR: Color = Color::new(15, 15, 20, 255);
+const FLOOR_COLOR: Color = Color::new(45, 45, 55, 255);
+const BORDER_COLOR: Color = Color::LIGHTGRAY;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MinimapShape {
+    Circle,
+    Square,
+}
+
+impl MinimapShape {
+    pub const ALL: [MinimapShape; 2] = [MinimapShape::Circle, MinimapShape::Square];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MinimapShape::Circle => "circulo",
+            MinimapShape::Square => "cuadrado",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MinimapShape> {
+        MinimapShape::ALL.into_iter().find(|shape| shape.name() == name)
+    }
+
+    // Texto de la pantalla de opciones
+    pub fn label(self) -> &'static str {
+        match self {
+            MinimapShape::Circle => "círculo",
+            MinimapShape::Square => "cuadrado",
+        }
+    }
+}
+
+pub struct Minimap {
+    explored: Vec<Vec<bool>>,
+    block_size: usize,
+    zoom: f32, // píxeles del minimapa por celda
+}
+
+// Marco del minimapa en pantalla y la transformación entre sus píxeles y
+// el mundo
+struct Frame {
+    center: Vector2,
+    radius: f32,
+    shape: MinimapShape,
+    origin: Vector2, // punto del mundo en el centro del marco
+    scale: f32,      // píxeles por unidad del mundo
+    rotation: f32,
+}
+
+impl Frame {
+    fn contains(&self, point: Vector2) -> bool {
+        let offset = point - self.center;
+        match self.shape {
+            MinimapShape::Circle => offset.length() <= self.radius,
+            MinimapShape::Square => offset.x.abs() <= self.radius && offset.y.abs() <= self.radius,
+        }
+    }
+
+    fn to_world(&self, point: Vector2) -> Vector2 {
+        self.origin + rotate(point - self.center, self.rotation) / self.scale
+    }
+
+    fn to_screen(&self, world: Vector2) -> Vector2 {
+        self.center + rotate(world - self.origin, -self.rotation) * self.scale
+    }
+}
+
+fn rotate(v: Vector2, angle: f32) -> Vector2 {
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+impl Minimap {
+    pub fn new(maze: &Maze, block_size: usize) -> Self {
+        let explored = maze.iter().map(|row| vec![false; row.len()]).collect();
+        Minimap { explored, block_size, zoom: DEFAULT_ZOOM }
+    }
+
+    // Pasos positivos acercan, negativos alejan
+    pub fn zoom(&mut self, steps: i32) {
+        self.zoom = (self.zoom * ZOOM_STEP.powi(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    pub fn is_explored(&self, world: Vector2) -> bool {
+        let bs = self.block_size as f32;
+        if world.x < 0.0 || world.y < 0.0 {
+            return false;
+        }
+        let (col, row) = ((world.x / bs) as usize, (world.y / bs) as usize);
+        self.explored.get(row).and_then(|cells| cells.get(col)).copied().unwrap_or(false)
+    }
+
+    // Marca como vistas las celdas que cruzan rayos dentro del campo de
+    // visión, hasta la primera pared (que también queda vista)
+    pub fn explore(&mut self, maze: &Maze, player: &Player) {
+        let bs = self.block_size as f32;
+        let step = bs / 4.0;
+        let range = EXPLORE_RANGE * bs;
+        for ray in 0..=EXPLORE_RAYS {
+            let a = player.a - player.fov / 2.0 + player.fov * ray as f32 / EXPLORE_RAYS as f32;
+            let direction = Vector2::new(a.cos(), a.sin());
+            let mut d = 0.0;
+            while d <= range {
+                let point = player.pos + direction * d;
+                if point.x < 0.0 || point.y < 0.0 {
+                    break;
+                }
+                let (col, row) = ((point.x / bs) as usize, (point.y / bs) as usize);
+                let Some(cell) = maze.get(row).and_then(|cells| cells.get(col)) else { break };
+                self.explored[row][col] = true;
+                if cell.is_solid() {
+                    break;
+                }
+                d += step;
+            }
+        }
+    }
+
+    // `markers` son puntos del mundo con su color (coleccionables,
+    // enemigos); `path` es el camino de la pista
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &self,
+        framebuffer: &mut Framebuffer,
+        maze: &Maze,
+        player: &Player,
+        texture_cache: &TextureManager,
+        settings: &Settings,
+        markers: &[(Vector2, Color)],
+        path: &[Vector2],
+    ) {
+        let bs = self.block_size as f32;
+        let side = (framebuffer.height as f32 * SIZE).round();
+        let frame = Frame {
+            center: Vector2::new(MARGIN + side / 2.0, MARGIN + side / 2.0),
+            radius: side / 2.0,
+            shape: settings.minimap_shape,
+            origin: player.pos,
+            scale: self.zoom / bs,
+            // Girando, lo que el jugador tiene de frente queda hacia arriba
+            rotation: if settings.minimap_rotate { player.a + PI / 2.0 } else { 0.0 },
+        };
+
+        for py in 0..side as u32 {
+            for px in 0..side as u32 {
+                let screen = Vector2::new(MARGIN + px as f32 + 0.5, MARGIN + py as f32 + 0.5);
+                if !frame.contains(screen) {
+                    continue;
+                }
+                let world = frame.to_world(screen);
+                let color = if self.is_explored(world) {
+                    let (col, row) = ((world.x / bs) as usize, (world.y / bs) as usize);
+                    let cell = maze[row][col];
+                    let tx = (world.x.rem_euclid(bs) / bs * 128.0) as u32;
+                    let ty = (world.y.rem_euclid(bs) / bs * 128.0) as u32;
+                    cell_color(cell, tx, ty, texture_cache)
+                } else {
+                    FOG_COLOR
+                };
+                framebuffer.set_current_color(color);
+                framebuffer.set_pixel(MARGIN as u32 + px, MARGIN as u32 + py);
+            }
+        }
+
+        framebuffer.set_current_color(HINT_COLOR);
+        for pair in path.windows(2) {
+            clipped_line(framebuffer, &frame, frame.to_screen(pair[0]), frame.to_screen(pair[1]));
+        }
+
+        for &(pos, color) in markers {
+            let screen = frame.to_screen(pos);
+            if self.is_explored(pos) && frame.contains(screen) {
+                framebuffer.set_current_color(color);
+                dot(framebuffer, screen, 2);
+            }
+        }
+
+        // Cono de visión y el jugador en el centro
+        let reach = frame.radius * 0.6 / frame.scale;
+        framebuffer.set_current_color(Color::WHITE);
+        for side in [-0.5, 0.5] {
+            let a = player.a + player.fov * side;
+            let edge = player.pos + Vector2::new(a.cos(), a.sin()) * reach;
+            clipped_line(framebuffer, &frame, frame.center, frame.to_screen(edge));
+        }
+        framebuffer.set_current_color(Color::RED);
+        dot(framebuffer, frame.center, 3);
+
+        draw_border(framebuffer, &frame);
+    }
+}
+
+// Mismo aspecto que las paredes del mundo si tienen textura
+fn cell_color(cell: Cell, tx: u32, ty: u32, texture_cache: &TextureManager) -> Color {
+    match cell.texture() {
+        Some(texture) if texture_cache.images.contains_key(&texture) => {
+            cell_to_texture_color(texture_cache, cell, tx, ty)
+        }
+        _ => cell.minimap_color().unwrap_or(FLOOR_COLOR),
+    }
+}
+
+// Línea que solo se dibuja dentro del marco
+fn clipped_line(framebuffer: &mut Framebuffer, frame: &Frame, from: Vector2, to: Vector2) {
+    let length = (to - from).length();
+    let steps = length.ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        let point = from + (to - from) * (i as f32 / steps as f32);
+        if frame.contains(point) {
+            framebuffer.set_pixel(point.x as u32, point.y as u32);
+        }
+    }
+}
+
+fn dot(framebuffer: &mut Framebuffer, center: Vector2, radius: i32) {
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy <= radius * radius {
+                framebuffer.set_pixel((center.x as i32 + dx) as u32, (center.y as i32 + dy) as u32);
+            }
+        }
+    }
+}
+
+fn draw_border(framebuffer: &mut Framebuffer, frame: &Frame) {
+    framebuffer.set_current_color(BORDER_COLOR);
+    match frame.shape {
+        MinimapShape::Circle => {
+            let points = (2.0 * PI * frame.radius).ceil() as usize;
+            for i in 0..points {
+                let a = 2.0 * PI * i as f32 / points as f32;
+                let point = frame.center + Vector2::new(a.cos(), a.sin()) * frame.radius;
+                framebuffer.set_pixel(point.x as u32, point.y as u32);
+            }
+        }
+        MinimapShape::Square => {
+            let (left, top) = (frame.center.x - frame.radius, frame.center.y - frame.radius);
+            let side = (frame.radius * 2.0) as u32;
+            for i in 0..=side {
+                framebuffer.set_pixel(left as u32 + i, top as u32);
+                framebuffer.set_pixel(left as u32 + i, top as u32 + side);
+                framebuffer.set_pixel(left as u32, top as u32 + i);
+                framebuffer.set_pixel(left as u32 + side, top as u32 + i);
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// pathfinding.rs
+//
+// Búsqueda de caminos sobre la grilla del laberinto: A* entre dos celdas,
+// Dijkstra hacia la celda más cercana que cumpla una condición y flood fill
+// de alcance. Los caminos se devuelven como celdas y se pueden suavizar a
+// puntos del mundo con línea de visión, teniendo en cuenta el radio del
+// agente para que los atajos no rocen las esquinas.
+//
+// Un `Pathfinder` guarda sus buffers entre búsquedas para poder usarse en
+// cada frame con varios agentes sin reservar memoria.
+
+use raylib::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::cell::Cell;
+use crate::maze::Maze;
+
+pub type CellPos = (usize, usize); // (columna, fila)
+
+// Costos enteros: 10 por paso recto, 14 en diagonal (≈ 10·√2)
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+// Atravesar una puerta cuesta como dar varios pasos más
+const DOOR_COST: u32 = 30;
+
+#[derive(Clone, Copy, Default)]
+pub struct PathOptions {
+    pub diagonal: bool,
+    pub doors: bool, // las puertas se pueden cruzar (con costo extra)
+}
+
+pub struct Pathfinder {
+    width: usize,
+    height: usize,
+    cost: Vec<u32>,
+    came_from: Vec<usize>,
+    // Marca de la búsqueda en la que se visitó cada celda; evita limpiar
+    // los buffers en cada llamada
+    visited: Vec<u32>,
+    search: u32,
+    heap: BinaryHeap<Reverse<(u32, usize)>>,
+    queue: VecDeque<usize>,
+}
+
+impl Pathfinder {
+    pub fn new(width: usize, height: usize) -> Self {
+        Pathfinder {
+            width,
+            height,
+            cost: vec![0; width * height],
+            came_from: vec![0; width * height],
+            visited: vec![0; width * height],
+            search: 0,
+            heap: BinaryHeap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn for_maze(maze: &Maze) -> Self {
+        Pathfinder::new(maze.first().map_or(0, Vec::len), maze.len())
+    }
+
+    fn begin(&mut self, maze: &Maze) {
+        let (width, height) = (maze.first().map_or(0, Vec::len), maze.len());
+        if width != self.width || height != self.height {
+            *self = Pathfinder::new(width, height);
+        }
+        self.search = self.search.wrapping_add(1);
+        if self.search == 0 {
+            self.visited.fill(0);
+            self.search = 1;
+        }
+        self.heap.clear();
+        self.queue.clear();
+    }
+
+    fn index(&self, (col, row): CellPos) -> usize {
+        row * self.width + col
+    }
+
+    fn pos(&self, index: usize) -> CellPos {
+        (index % self.width, index / self.width)
+    }
+
+    // Costo extra de entrar a la celda, o None si no se puede pisar
+    fn entry_cost(maze: &Maze, (col, row): CellPos, options: PathOptions) -> Option<u32> {
+        match maze.get(row).and_then(|cells| cells.get(col))? {
+            Cell::Door if options.doors => Some(DOOR_COST),
+            cell if cell.is_solid() => None,
+            _ => Some(0),
+        }
+    }
+
+    // Vecinos transitables con el costo del paso; en diagonal no se cortan
+    // esquinas de pared
+    fn neighbors(&self, maze: &Maze, index: usize, options: PathOptions, out: &mut Vec<(usize, u32)>) {
+        out.clear();
+        let (col, row) = self.pos(index);
+        let (col, row) = (col as isize, row as isize);
+        let open = |c: isize, r: isize| {
+            if c < 0 || r < 0 {
+                return None;
+            }
+            Self::entry_cost(maze, (c as usize, r as usize), options)
+        };
+
+        for (dc, dr) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(extra) = open(col + dc, row + dr) {
+                out.push((self.index(((col + dc) as usize, (row + dr) as usize)), STRAIGHT_COST + extra));
+            }
+        }
+        if options.diagonal {
+            for (dc, dr) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                if open(col + dc, row).is_none() || open(col, row + dr).is_none() {
+                    continue;
+                }
+                if let Some(extra) = open(col + dc, row + dr) {
+                    out.push((self.index(((col + dc) as usize, (row + dr) as usize)), DIAGONAL_COST + extra));
+                }
+            }
+        }
+    }
+
+    fn heuristic(&self, a: usize, b: usize, options: PathOptions) -> u32 {
+        let (ac, ar) = self.pos(a);
+        let (bc, br) = self.pos(b);
+        let dx = ac.abs_diff(bc) as u32;
+        let dy = ar.abs_diff(br) as u32;
+        if options.diagonal {
+            // Distancia octil
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        } else {
+            STRAIGHT_COST * (dx + dy)
+        }
+    }
+
+    fn build_path(&self, start: usize, goal: usize) -> Vec<CellPos> {
+        let mut path = vec![self.pos(goal)];
+        let mut current = goal;
+        while current != start {
+            current = self.came_from[current];
+            path.push(self.pos(current));
+        }
+        path.reverse();
+        path
+    }
+
+    fn in_bounds(&self, (col, row): CellPos) -> bool {
+        col < self.width && row < self.height
+    }
+
+    // Camino de celdas de `start` a `goal`, ambos incluidos
+    pub fn find_path(&mut self, maze: &Maze, start: CellPos, goal: CellPos, options: PathOptions) -> Option<Vec<CellPos>> {
+        self.begin(maze);
+        if !self.in_bounds(start) || !self.in_bounds(goal) || Self::entry_cost(maze, goal, options).is_none() {
+            return None;
+        }
+        let (start, goal) = (self.index(start), self.index(goal));
+
+        self.visited[start] = self.search;
+        self.cost[start] = 0;
+        self.heap.push(Reverse((self.heuristic(start, goal, options), start)));
+
+        let mut neighbors = Vec::with_capacity(8);
+        while let Some(Reverse((priority, current))) = self.heap.pop() {
+            if current == goal {
+                return Some(self.build_path(start, goal));
+            }
+            // Entrada vieja de la cola: ya se encontró un camino mejor
+            if priority > self.cost[current] + self.heuristic(current, goal, options) {
+                continue;
+            }
+
+            self.neighbors(maze, current, options, &mut neighbors);
+            for &(next, step) in &neighbors {
+                let cost = self.cost[current] + step;
+                if self.visited[next] != self.search || cost < self.cost[next] {
+                    self.visited[next] = self.search;
+                    self.cost[next] = cost;
+                    self.came_from[next] = current;
+                    self.heap.push(Reverse((cost + self.heuristic(next, goal, options), next)));
+                }
+            }
+        }
+        None
+    }
+
+    // Camino a la celda más barata de alcanzar que cumpla `is_goal`
+    pub fn nearest(
+        &mut self,
+        maze: &Maze,
+        start: CellPos,
+        options: PathOptions,
+        is_goal: impl Fn(CellPos) -> bool,
+    ) -> Option<Vec<CellPos>> {
+        self.begin(maze);
+        if !self.in_bounds(start) {
+            return None;
+        }
+        let start = self.index(start);
+
+        self.visited[start] = self.search;
+        self.cost[start] = 0;
+        self.heap.push(Reverse((0, start)));
+
+        let mut neighbors = Vec::with_capacity(8);
+        while let Some(Reverse((cost, current))) = self.heap.pop() {
+            if cost > self.cost[current] {
+                continue;
+            }
+            if is_goal(self.pos(current)) {
+                return Some(self.build_path(start, current));
+            }
+
+            self.neighbors(maze, current, options, &mut neighbors);
+            for &(next, step) in &neighbors {
+                let cost = cost + step;
+                if self.visited[next] != self.search || cost < self.cost[next] {
+                    self.visited[next] = self.search;
+                    self.cost[next] = cost;
+                    self.came_from[next] = current;
+                    self.heap.push(Reverse((cost, next)));
+                }
+            }
+        }
+        None
+    }
+
+    // Celdas alcanzables desde `start`, indexadas como `[fila][columna]`
+    pub fn reachable(&mut self, maze: &Maze, start: CellPos, options: PathOptions) -> Vec<Vec<bool>> {
+        self.begin(maze);
+        let mut reached = vec![vec![false; self.width]; self.height];
+        if !self.in_bounds(start) {
+            return reached;
+        }
+        let start = self.index(start);
+        self.visited[start] = self.search;
+        self.queue.push_back(start);
+
+        let mut neighbors = Vec::with_capacity(8);
+        while let Some(current) = self.queue.pop_front() {
+            let (col, row) = self.pos(current);
+            reached[row][col] = true;
+            self.neighbors(maze, current, options, &mut neighbors);
+            for &(next, _) in &neighbors {
+                if self.visited[next] != self.search {
+                    self.visited[next] = self.search;
+                    self.queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+}
+
+pub fn world_to_cell(pos: Vector2, block_size: usize) -> CellPos {
+    ((pos.x.max(0.0) as usize) / block_size, (pos.y.max(0.0) as usize) / block_size)
+}
+
+pub fn cell_center((col, row): CellPos, block_size: usize) -> Vector2 {
+    Vector2::new(
+        (col * block_size + block_size / 2) as f32,
+        (row * block_size + block_size / 2) as f32,
+    )
+}
+
+// ¿Cabe un círculo de `radius` en `point` sin tocar celdas que no se
+// pueden pisar? Fuera del mapa cuenta como pared.
+fn clear_of_walls(maze: &Maze, point: Vector2, radius: f32, block_size: usize, options: PathOptions) -> bool {
+    let bs = block_size as f32;
+    let first_col = ((point.x - radius) / bs).floor() as i64;
+    let last_col = ((point.x + radius) / bs).floor() as i64;
+    let first_row = ((point.y - radius) / bs).floor() as i64;
+    let last_row = ((point.y + radius) / bs).floor() as i64;
+
+    (first_row..=last_row).all(|row| {
+        (first_col..=last_col).all(|col| {
+            let open = col >= 0
+                && row >= 0
+                && Pathfinder::entry_cost(maze, (col as usize, row as usize), options).is_some();
+            if open {
+                return true;
+            }
+            let (min_x, min_y) = (col as f32 * bs, row as f32 * bs);
+            let dx = point.x - point.x.clamp(min_x, min_x + bs);
+            let dy = point.y - point.y.clamp(min_y, min_y + bs);
+            // Si el centro está en la celda, dx = dy = 0 y no cabe; un agente
+            // que las colisiones dejaron justo a `radius` de la pared sí cabe
+            (dx != 0.0 || dy != 0.0) && (dx * dx + dy * dy).sqrt() >= radius - 0.01
+        })
+    })
+}
+
+// ¿Puede ir en línea recta de `from` a `to` un agente de radio `radius`
+// sin tocar paredes? Con radio 0 es la línea de visión de un punto.
+pub fn line_of_sight(maze: &Maze, from: Vector2, to: Vector2, radius: f32, block_size: usize, options: PathOptions) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    // Pasos de un décimo de celda (o de medio radio, si es más chico):
+    // suficiente para no saltarse esquinas
+    let step = (block_size as f32 / 10.0).min((radius * 0.5).max(1.0));
+    let steps = (length / step).ceil().max(1.0) as usize;
+    (0..=steps).all(|i| {
+        let t = i as f32 / steps as f32;
+        let point = Vector2::new(from.x + dx * t, from.y + dy * t);
+        clear_of_walls(maze, point, radius, block_size, options)
+    })
+}
+
+// Convierte un camino de celdas en puntos del mundo, saltando los
+// intermedios que un agente de radio `radius` alcanza en línea recta
+pub fn smooth_path(maze: &Maze, path: &[CellPos], radius: f32, block_size: usize, options: PathOptions) -> Vec<Vector2> {
+    let points: Vec<Vector2> = path.iter().map(|&cell| cell_center(cell, block_size)).collect();
+    let Some(&first) = points.first() else { return Vec::new() };
+
+    let mut waypoints = vec![first];
+    let mut anchor = 0;
+    while anchor + 1 < points.len() {
+        let mut next = anchor + 1;
+        while next + 1 < points.len() && line_of_sight(maze, points[anchor], points[next + 1], radius, block_size, options) {
+            next += 1;
+        }
+        waypoints.push(points[next]);
+        anchor = next;
+    }
+    waypoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::grid;
+
+    const BS: usize = 100;
+
+    // El camino de abajo a la derecha da toda la vuelta por arriba
+    fn loop_maze() -> Maze {
+        grid(&["#######", "#     #", "# ### #", "#   # #", "#######"])
+    }
+
+    fn assert_connected(path: &[CellPos], diagonal: bool) {
+        for pair in path.windows(2) {
+            let (dc, dr) = (pair[0].0.abs_diff(pair[1].0), pair[0].1.abs_diff(pair[1].1));
+            let step_ok = dc + dr == 1 || (diagonal && dc == 1 && dr == 1);
+            assert!(step_ok, "{:?} -> {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn finds_the_shortest_path_around_walls() {
+        let maze = loop_maze();
+        let mut pathfinder = Pathfinder::for_maze(&maze);
+        let path = pathfinder.find_path(&maze, (1, 3), (5, 3), PathOptions::default()).unwrap();
+        assert_eq!(path.first(), Some(&(1, 3)));
+        assert_eq!(path.last(), Some(&(5, 3)));
+        assert_eq!(path.len(), 9);
+        assert_connected(&path, false);
+
+        // Los buffers se reutilizan entre búsquedas
+        let path = pathfinder.find_path(&maze, (1, 1), (5, 1), PathOptions::default()).unwrap();
+        assert_eq!(path, vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)]);
+        assert_eq!(pathfinder.find_path(&maze, (2, 3), (2, 3), PathOptions::default()), Some(vec![(2, 3)]));
+    }
+
+    #[test]
+    fn rejects_walls_unreachable_cells_and_out_of_bounds() {
+        let maze = grid(&["#####", "# # #", "#####"]);
+        let mut pathfinder = Pathfinder::for_maze(&maze);
+        let options = PathOptions::default();
+        assert!(pathfinder.find_path(&maze, (1, 1), (2, 1), options).is_none());
+        assert!(pathfinder.find_path(&maze, (1, 1), (3, 1), options).is_none());
+        assert!(pathfinder.find_path(&maze, (1, 1), (9, 9), options).is_none());
+        assert!(pathfinder.find_path(&maze, (9, 9), (1, 1), options).is_none());
+    }
+
+    #[test]
+    fn diagonals_do_not_cut_wall_corners() {
+        let open = grid(&["#####", "#   #", "#   #", "#   #", "#####"]);
+        let mut pathfinder = Pathfinder::for_maze(&open);
+        let diagonal = PathOptions { diagonal: true, ..Default::default() };
+        assert_eq!(pathfinder.find_path(&open, (1, 1), (3, 3), diagonal), Some(vec![(1, 1), (2, 2), (3, 3)]));
+
+        let pillar = grid(&["#####", "#   #", "# # #", "#   #", "#####"]);
+        let path = pathfinder.find_path(&pillar, (1, 1), (3, 3), diagonal).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_connected(&path, false);
+    }
+
+    #[test]
+    fn crosses_doors_only_when_allowed() {
+        let maze = grid(&["#####", "# D #", "#####"]);
+        let mut pathfinder = Pathfinder::for_maze(&maze);
+        assert!(pathfinder.find_path(&maze, (1, 1), (3, 1), PathOptions::default()).is_none());
+        let doors = PathOptions { doors: true, ..Default::default() };
+        assert_eq!(pathfinder.find_path(&maze, (1, 1), (3, 1), doors), Some(vec![(1, 1), (2, 1), (3, 1)]));
+        assert!(!pathfinder.reachable(&maze, (1, 1), PathOptions::default())[1][3]);
+        assert!(pathfinder.reachable(&maze, (1, 1), doors)[1][3]);
+    }
+
+    #[test]
+    fn nearest_goes_by_path_cost_not_distance() {
+        let maze = loop_maze();
+        let mut pathfinder = Pathfinder::for_maze(&maze);
+        let targets = [(5, 3), (1, 1)];
+        let path = pathfinder.nearest(&maze, (3, 3), PathOptions::default(), |cell| targets.contains(&cell)).unwrap();
+        assert_eq!(path, vec![(3, 3), (2, 3), (1, 3), (1, 2), (1, 1)]);
+        assert!(pathfinder.nearest(&maze, (3, 3), PathOptions::default(), |cell| cell == (3, 2)).is_none());
+        assert_eq!(pathfinder.nearest(&maze, (3, 3), PathOptions::default(), |cell| cell == (3, 3)), Some(vec![(3, 3)]));
+    }
+
+    #[test]
+    fn reachable_marks_only_the_connected_area() {
+        let maze = grid(&["######", "#  # #", "######"]);
+        let mut pathfinder = Pathfinder::for_maze(&maze);
+        let reached = pathfinder.reachable(&maze, (1, 1), PathOptions::default());
+        assert!(reached[1][1] && reached[1][2]);
+        assert!(!reached[1][3] && !reached[1][4]);
+        assert_eq!(reached.iter().flatten().filter(|&&inside| inside).count(), 2);
+    }
+
+    #[test]
+    fn line_of_sight_accounts_for_the_radius() {
+        let corridor = loop_maze();
+        let options = PathOptions::default();
+        let (a, b) = (Vector2::new(150.0, 150.0), Vector2::new(550.0, 150.0));
+        assert!(line_of_sight(&corridor, a, b, 0.0, BS, options));
+        assert!(line_of_sight(&corridor, a, b, 20.0, BS, options));
+        assert!(!line_of_sight(&corridor, a, Vector2::new(550.0, 350.0), 0.0, BS, options));
+
+        // Pasa a unos 12 de la esquina (300, 200): un punto sí, un círculo de radio 20 no
+        let open = grid(&["#######", "#     #", "###   #", "#######"]);
+        let (from, to) = (Vector2::new(150.0, 150.0), Vector2::new(550.0, 250.0));
+        assert!(line_of_sight(&open, from, to, 0.0, BS, options));
+        assert!(!line_of_sight(&open, from, to, 20.0, BS, options));
+        assert!(line_of_sight(&open, from, to, 10.0, BS, options));
+    }
+
+    #[test]
+    fn smoothing_keeps_agents_off_corners() {
+        let open = grid(&["#######", "#     #", "###   #", "#######"]);
+        let path = [(1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (5, 2)];
+        let options = PathOptions::default();
+        assert_eq!(smooth_path(&open, &path, 0.0, BS, options), vec![Vector2::new(150.0, 150.0), Vector2::new(550.0, 250.0)]);
+        assert_eq!(
+            smooth_path(&open, &path, 20.0, BS, options),
+            vec![Vector2::new(150.0, 150.0), Vector2::new(550.0, 150.0), Vector2::new(550.0, 250.0)]
+        );
+        assert!(smooth_path(&open, &[], 20.0, BS, options).is_empty());
+    }
+
+    #[test]
+    fn converts_between_world_and_cells() {
+        assert_eq!(world_to_cell(Vector2::new(250.0, 99.0), BS), (2, 0));
+        assert_eq!(world_to_cell(Vector2::new(-5.0, 100.0), BS), (0, 1));
+        assert_eq!(cell_center((2, 3), BS), Vector2::new(250.0, 350.0));
+    }
+}
@@ -0,0 +1,355 @@
+// play.rs
+//
+// Una partida de un nivel: el jugador, los coleccionables, los enemigos y
+// el arma. `handle_input` atiende una vez por frame lo que se aprieta y el
+// mouse; `update` avanza la simulación un paso fijo y devuelve cómo terminó
+// el nivel cuando termina; `render` dibuja la escena y el HUD en el framebuffer sin
+// mostrarlo, para que el estado de pausa pueda dibujar encima.
+
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::audio::Audio;
+use crate::camera::{CameraEffects, ViewOffset};
+use crate::cell::Cell;
+use crate::config::Settings;
+use crate::enemy::{Enemy, CONTACT_DAMAGE};
+use crate::framebuffer::Framebuffer;
+use crate::hint::Hint;
+use crate::input::Action;
+use crate::collision::{move_circle, slide};
+use crate::maze::Level;
+use crate::minimap::Minimap;
+use crate::pathfinding::{cell_center, Pathfinder};
+use crate::player::{process_events, process_mouse, Player, MAX_HEALTH, PLAYER_RADIUS};
+use crate::sprites::{render_sprites, Sprite};
+use crate::textures::{TextureManager, FLOOR_TEXTURE, SKY_TEXTURE};
+use crate::weapon::{Weapon, WeaponKind};
+use crate::{render_world, BLOCK_SIZE, COLLECT_SOUND, PICKUP_RANGE, SHOT_SOUND, SPRITE_HUM};
+
+pub enum LevelOutcome {
+    Won {
+        next_level: Option<String>, // el `next` de la cabecera del nivel
+        next_entry: Option<String>, // spawn del siguiente nivel según la salida que se tomó
+    },
+    Died,
+    Quit,
+    Restart,
+}
+
+pub struct PlaySession {
+    level: Level,
+    player: Player,
+    sprites: Vec<Sprite>,
+    enemies: Vec<Enemy>,
+    pathfinder: Pathfinder,
+    hint: Hint,
+    weapon: Weapon,
+    camera: CameraEffects,
+    hurting: bool, // el paso anterior quitó vida
+    elapsed: f32, // segundos jugados, sin contar las pausas
+    trigger_message: Option<String>,
+    show_map: bool,
+    minimap: Minimap,
+}
+
+impl PlaySession {
+    pub fn new(
+        level: Level,
+        entry: Option<&str>,
+        settings: &Settings,
+        texture_cache: &mut TextureManager,
+        audio: &mut Audio,
+    ) -> Self {
+        let block_size = BLOCK_SIZE;
+        let meta = &level.meta;
+
+        // Sin música propia se mantiene la que venía sonando
+        if !meta.music.is_empty() {
+            let playlist: Vec<&str> = meta.music.iter().map(String::as_str).collect();
+            audio.play_music(&playlist);
+        }
+
+        // Cada nivel empieza con las texturas de base: lo que reemplazó el
+        // anterior no se arrastra
+        texture_cache.reset_level();
+        let level_textures = meta.textures.iter()
+            .map(|(ch, path)| (*ch, path))
+            .chain(meta.sky_texture.iter().map(|path| (SKY_TEXTURE, path)))
+            .chain(meta.floor_texture.iter().map(|path| (FLOOR_TEXTURE, path)));
+        for (ch, path) in level_textures {
+            if let Err(e) = texture_cache.load(ch, path) {
+                eprintln!("No se pudo cargar la textura {}: {}", path, e);
+            }
+        }
+
+        let spawn = level.spawn(entry);
+        let spawn_pos = Vector2::new(
+            (spawn.col * block_size + block_size / 2) as f32,
+            (spawn.row * block_size + block_size / 2) as f32,
+        );
+        let player = Player::new(spawn_pos, spawn.facing.unwrap_or(PI / 3.0), settings.fov_radians());
+
+        let mut sprites = Vec::new();
+        for (row_index, row) in level.maze.iter().enumerate() {
+            for (col_index, &cell) in row.iter().enumerate() {
+                if let Cell::Pickup { kind } = cell {
+                    let x = (col_index * block_size + block_size / 2) as f32;
+                    let y = (row_index * block_size + block_size / 2) as f32;
+                    if let Some(mut sprite) = Sprite::from_char(kind, x, y, texture_cache) {
+                        sprite.collected = false;
+                        audio.attach_to_sprite(SPRITE_HUM, sprites.len(), &sprite);
+                        sprites.push(sprite);
+                    }
+                }
+            }
+        }
+
+        let mut enemies = Vec::new();
+        for spawn in &level.enemies {
+            let pos = cell_center((spawn.col, spawn.row), block_size);
+            if let Some(sprite) = Sprite::from_char(spawn.kind, pos.x, pos.y, texture_cache) {
+                let patrol = spawn.patrol.iter().map(|&cell| cell_center(cell, block_size)).collect();
+                enemies.push(Enemy::new(sprite, patrol));
+            }
+        }
+
+        PlaySession {
+            pathfinder: Pathfinder::for_maze(&level.maze),
+            hint: Hint::new(&level.maze),
+            weapon: Weapon::new(),
+            camera: CameraEffects::new(),
+            hurting: false,
+            elapsed: 0.0,
+            trigger_message: None,
+            show_map: true,
+            minimap: Minimap::new(&level.maze, block_size),
+            level,
+            player,
+            sprites,
+            enemies,
+        }
+    }
+
+    // Tiempo del nivel con la penalización de las pistas
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed + self.hint.penalty()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.level.meta.name
+    }
+
+    // Abre la puerta que está justo delante del jugador; queda abierta
+    // hasta que se reinicia el nivel
+    fn open_door(&mut self) {
+        let reach = BLOCK_SIZE as f32 * 0.8;
+        let player = &self.player;
+        let x = player.pos.x + reach * player.a.cos();
+        let y = player.pos.y + reach * player.a.sin();
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        let (col, row) = (x as usize / BLOCK_SIZE, y as usize / BLOCK_SIZE);
+        if let Some(cell) = self.level.maze.get_mut(row).and_then(|cells| cells.get_mut(col))
+            && *cell == Cell::Door
+        {
+            *cell = Cell::Empty;
+            self.camera.shake(0.3, 0.2, 2.0);
+        }
+    }
+
+    // Lo que se aprieta (y no se mantiene) se lee una vez por frame, para
+    // que no se pierda ni se repita según cuántos pasos toquen en el frame
+    pub fn handle_input(&mut self, window: &RaylibHandle, settings: &Settings, audio: &mut Audio) -> Option<LevelOutcome> {
+        if window.window_should_close() {
+            return Some(LevelOutcome::Quit);
+        }
+        let bindings = &settings.bindings;
+        if bindings.pressed(window, Action::Restart) {
+            return Some(LevelOutcome::Restart);
+        }
+        if bindings.pressed(window, Action::Use) { self.open_door(); }
+        if bindings.pressed(window, Action::Jump) { self.player.jump(); }
+        if bindings.pressed(window, Action::Map) { self.show_map = !self.show_map; }
+        if bindings.pressed(window, Action::MapZoomIn) { self.minimap.zoom(1); }
+        if bindings.pressed(window, Action::MapZoomOut) { self.minimap.zoom(-1); }
+        if bindings.pressed(window, Action::Mute) { audio.toggle_mute(); }
+        if bindings.pressed(window, Action::Hint) { self.hint.toggle(); }
+        if bindings.pressed(window, Action::WeaponPistol) { self.weapon.select(WeaponKind::Pistol); }
+        if bindings.pressed(window, Action::WeaponLauncher) { self.weapon.select(WeaponKind::Launcher); }
+
+        // El campo de visión se puede cambiar desde las opciones en plena partida
+        self.player.fov = settings.fov_radians();
+        process_mouse(&mut self.player, window, settings);
+        None
+    }
+
+    // Un paso de la simulación de `dt` segundos
+    pub fn update(
+        &mut self,
+        window: &RaylibHandle,
+        dt: f32,
+        settings: &Settings,
+        audio: &mut Audio,
+        texture_cache: &TextureManager,
+    ) -> Option<LevelOutcome> {
+        let block_size = BLOCK_SIZE;
+        let level = &self.level;
+        let maze = &level.maze;
+        let player = &mut self.player;
+        let health_before = player.health;
+        self.elapsed += dt;
+
+        process_events(player, window, settings, dt);
+
+        // Al chocar se pierde la velocidad contra la pared y se sigue
+        // deslizando a lo largo de ella
+        let collision = move_circle(maze, player.pos, player.velocity * dt, PLAYER_RADIUS, block_size);
+        player.pos = collision.pos;
+        if let Some(normal) = collision.normal {
+            player.velocity = slide(player.velocity, normal);
+        }
+
+        // Triggers de los mapas de Tiled: `teleport` lleva al spawn `target`
+        // y `message` muestra su texto mientras se está dentro
+        self.trigger_message = None;
+        if let Some(trigger) = level.trigger_at(player.pos.x / block_size as f32, player.pos.y / block_size as f32) {
+            match (trigger.action.as_str(), trigger.target.as_deref()) {
+                ("teleport", Some(target)) => {
+                    let spawn = level.spawn(Some(target));
+                    player.pos.x = (spawn.col * block_size + block_size / 2) as f32;
+                    player.pos.y = (spawn.row * block_size + block_size / 2) as f32;
+                    if let Some(facing) = spawn.facing { player.a = facing; }
+                    player.velocity = Vector2::zero();
+                }
+                ("message", Some(text)) => self.trigger_message = Some(text.to_string()),
+                _ => {}
+            }
+        }
+
+        // Colisión con sprites -> desaparecen al tocarlos
+        for sprite in &mut self.sprites {
+            if !sprite.collected {
+                let dx = player.pos.x - sprite.x;
+                let dy = player.pos.y - sprite.y;
+                let dist = (dx*dx + dy*dy).sqrt();
+                if dist < PICKUP_RANGE {
+                    sprite.collected = true;
+                    audio.play_at(COLLECT_SOUND, sprite.pos);
+                }
+            }
+        }
+
+        // Disparo mientras se mantiene la acción
+        let weapon = &mut self.weapon;
+        if settings.bindings.down(window, Action::Fire) && weapon.fire(player, maze, &mut self.enemies, block_size, texture_cache) {
+            audio.play_at(SHOT_SOUND, player.pos);
+        }
+        weapon.update(dt, maze, &mut self.enemies, block_size);
+        self.enemies.retain(|enemy| !enemy.is_dead());
+
+        // Enemigos: se mueven y quitan vida mientras tocan al jugador
+        for enemy in &mut self.enemies {
+            enemy.update(dt, maze, player, block_size, &mut self.pathfinder);
+            if enemy.touches(player) {
+                player.health -= CONTACT_DAMAGE * dt;
+            }
+        }
+        let (col, row) = (player.pos.x as usize / block_size, player.pos.y as usize / block_size);
+        // En el aire se pasa por encima de las trampas
+        if let Some(Cell::Hazard { kind }) = maze.get(row).and_then(|cells| cells.get(col))
+            && !player.is_airborne()
+        {
+            player.health -= kind.damage() * dt;
+        }
+        if player.health <= 0.0 {
+            return Some(LevelOutcome::Died);
+        }
+
+        // El daño continuo (un enemigo encima, lava) sacude una sola vez al
+        // empezar, no en cada paso
+        let hurt = player.health < health_before;
+        if hurt && !self.hurting {
+            self.camera.shake(0.4, 0.25, 2.0);
+        }
+        self.hurting = hurt;
+        let speed = player.velocity.length() / settings.movement.max_speed;
+        self.camera.update(dt, speed, !player.is_airborne());
+        player.view = if settings.camera_effects { self.camera.offset() } else { ViewOffset::default() };
+
+        audio.update(player, &self.sprites);
+
+        // Con salidas en el mapa hay que llegar a una después de recolectar todo
+        if self.sprites.iter().all(|s| s.collected) {
+            let next_level = level.meta.next.clone();
+            if level.exits.is_empty() {
+                return Some(LevelOutcome::Won { next_level, next_entry: None });
+            }
+            if let Some(exit) = level.exit_at(col, row) {
+                return Some(LevelOutcome::Won { next_level, next_entry: exit.target_spawn.clone() });
+            }
+        }
+
+        self.minimap.explore(maze, player);
+        self.hint.update(maze, player, &self.sprites, block_size);
+        None
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, texture_cache: &TextureManager, settings: &Settings, lives: u32) {
+        let block_size = BLOCK_SIZE;
+        let level = &self.level;
+        let meta = &level.meta;
+        let player = &self.player;
+
+        framebuffer.clear();
+        let z_buffer = render_world(framebuffer, level, block_size, player, texture_cache);
+
+        let mut sprites_sorted: Vec<_> = self.sprites.iter().filter(|s| !s.collected).cloned()
+            .chain(self.enemies.iter().map(|enemy| enemy.sprite.clone()))
+            .chain(self.weapon.projectiles.iter().map(|projectile| projectile.sprite.clone()))
+            .collect();
+        sprites_sorted.sort_by(|a, b| {
+            let da = (a.x - player.pos.x).powi(2) + (a.y - player.pos.y).powi(2);
+            let db = (b.x - player.pos.x).powi(2) + (b.y - player.pos.y).powi(2);
+            db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        render_sprites(framebuffer, player, &sprites_sorted, &z_buffer, meta);
+        self.weapon.draw_overlay(framebuffer, texture_cache);
+
+        // Minimapa con la pista, los coleccionables (amarillo) y los enemigos (rojo)
+        if self.show_map {
+            let markers: Vec<_> = self.sprites.iter().filter(|sprite| !sprite.collected).map(|sprite| (sprite.pos, Color::YELLOW))
+                .chain(self.enemies.iter().map(|enemy| (enemy.sprite.pos, Color::RED)))
+                .collect();
+            self.minimap.draw(framebuffer, &level.maze, player, texture_cache, settings, &markers, self.hint.path());
+        }
+        self.hint.draw_arrow(framebuffer, player);
+
+        // Barra de vida; las vidas y el arma van en su propia línea arriba
+        // para que entren también con el ancho de render mínimo
+        let bar_y = framebuffer.height - 50;
+        let filled = (200.0 * (player.health / MAX_HEALTH).clamp(0.0, 1.0)) as u32;
+        for x in 0..200 {
+            framebuffer.set_current_color(if x < filled { Color::RED } else { Color::DARKGRAY });
+            for y in 0..10 {
+                framebuffer.set_pixel(10 + x, bar_y + y);
+            }
+        }
+        framebuffer.draw_text(&format!("Vidas: {}   Arma: {}", lives, self.weapon.kind.name()), 10, bar_y as i32 - 25, 20, Color::WHITE);
+
+        // Las pistas cuentan como tiempo extra
+        let elapsed = self.elapsed();
+        let mut hud = match meta.par_time {
+            Some(par) => format!("{}   Tiempo: {:.0} s / par {:.0} s", meta.name, elapsed, par),
+            None => format!("{}   Tiempo: {:.0} s", meta.name, elapsed),
+        };
+        if self.hint.uses > 0 {
+            hud.push_str(&format!("   (pistas: +{:.0} s)", self.hint.penalty()));
+        }
+        framebuffer.draw_text(&hud, 10, framebuffer.height as i32 - 30, 20, Color::WHITE);
+        if let Some(text) = &self.trigger_message {
+            framebuffer.draw_text(text, 10, bar_y as i32 - 55, 24, Color::YELLOW);
+        }
+    }
+}
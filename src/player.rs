@@ -1,17 +1,71 @@
 use raylib::prelude::*;
 use std::f32::consts::PI;
 
+use crate::camera::ViewOffset;
+use crate::config::Settings;
+use crate::input::Action;
+
+// Radio de colisión del jugador: la cámara nunca queda a menos de esto de
+// una pared
+pub const PLAYER_RADIUS: f32 = 20.0;
+pub const MAX_HEALTH: f32 = 100.0;
+
+// Altura de los ojos en alturas de pared: parado se ve justo la mitad
+pub const EYE_HEIGHT: f32 = 0.5;
+const CROUCH_DROP: f32 = 0.2;
+const CROUCH_RATE: f32 = 6.0; // cuánto tarda en agacharse: 1/6 s
+const CROUCH_SPEED: f32 = 0.5; // la velocidad máxima se multiplica agachado
+const JUMP_SPEED: f32 = 1.3; // alturas de pared por segundo al despegar
+const GRAVITY: f32 = 5.0;
+// La mirada vertical corre el horizonte hasta esta fracción de media pantalla
+const MAX_PITCH: f32 = 0.8;
+const PITCH_SPEED: f32 = 1.5; // con teclas, por segundo
+
+// Ajustes del movimiento; se pueden cambiar en el archivo de configuración
+#[derive(Clone, Debug, PartialEq)]
+pub struct Movement {
+    pub max_speed: f32,      // unidades por segundo caminando
+    pub acceleration: f32,   // unidades por segundo² al apretar una dirección
+    pub friction: f32,       // unidades por segundo² de frenado al soltar
+    pub run_multiplier: f32, // la velocidad máxima se multiplica al correr
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Movement { max_speed: 350.0, acceleration: 2500.0, friction: 2000.0, run_multiplier: 1.7 }
+    }
+}
+
 pub struct Player {
     pub pos: Vector2,
+    pub velocity: Vector2, // unidades por segundo
     pub a: f32,      // angle (direction the player is facing)
     pub fov: f32,    // field of view
     pub plane: Vector2, // camera plane vector
+    pub pitch: f32,  // positivo mira hacia arriba: el horizonte baja
+    pub z: f32,      // altura del salto sobre el piso
+    pub vz: f32,
+    pub crouch: f32, // 0 parado, 1 agachado del todo
+    pub view: ViewOffset, // balanceo y sacudidas de la cámara, solo para dibujar
+    pub health: f32,
 }
 
 impl Player {
     pub fn new(pos: Vector2, a: f32, fov: f32) -> Self {
         let plane = Self::compute_plane(a, fov);
-        Self { pos, a, fov, plane }
+        Self {
+            pos,
+            a,
+            fov,
+            plane,
+            velocity: Vector2::zero(),
+            pitch: 0.0,
+            z: 0.0,
+            vz: 0.0,
+            crouch: 0.0,
+            view: ViewOffset::default(),
+            health: MAX_HEALTH,
+        }
     }
 
     fn compute_plane(a: f32, fov: f32) -> Vector2 {
@@ -24,61 +78,170 @@ impl Player {
     pub fn update_plane(&mut self) {
         self.plane = Self::compute_plane(self.a, self.fov);
     }
-}
-
-pub fn process_events(player: &mut Player, rl: &RaylibHandle) {
-    const MOVE_SPEED: f32 = 10.0;
-    const ROTATION_SPEED: f32 = PI / 10.0;
-    const MOUSE_SENSITIVITY: f32 = 0.005;
-    const GAMEPAD_SENSITIVITY: f32 = 0.05; // para rotación analógica
-    const DEADZONE: f32 = 0.2; // para evitar drift del joystick
 
-    // --- Controles por teclado ---
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        player.a += ROTATION_SPEED;
+    // Hacia dónde mira la cámara al dibujar; apuntar y moverse usan `a`
+    pub fn view_angle(&self) -> f32 {
+        self.a + self.view.angle
     }
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        player.a -= ROTATION_SPEED;
+
+    // Altura de los ojos contando el salto, lo agachado y el balanceo
+    pub fn eye_height(&self) -> f32 {
+        EYE_HEIGHT + self.z - self.crouch * CROUCH_DROP + self.view.eye
     }
 
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) {
-        player.pos.x -= MOVE_SPEED * player.a.cos();
-        player.pos.y -= MOVE_SPEED * player.a.sin();
+    // Fila de la pantalla donde queda el horizonte; mirar arriba o abajo
+    // corre toda la escena (y-shearing) en lugar de rotar la cámara
+    pub fn horizon(&self, screen_height: u32) -> f32 {
+        let hh = screen_height as f32 / 2.0;
+        (hh + (self.pitch + self.view.pitch) * hh).round()
     }
-    if rl.is_key_down(KeyboardKey::KEY_UP) {
-        player.pos.x += MOVE_SPEED * player.a.cos();
-        player.pos.y += MOVE_SPEED * player.a.sin();
+
+    pub fn is_airborne(&self) -> bool {
+        self.z > 0.0
     }
 
-    // --- Controles por mouse (rotación) ---
-    let mouse_delta = rl.get_mouse_delta();
-    player.a -= mouse_delta.x * MOUSE_SENSITIVITY;
-
-    // --- Controles por gamepad ---
-    let gamepad_index = 0;
-    if rl.is_gamepad_available(gamepad_index) {
-        let left_x = rl.get_gamepad_axis_movement(gamepad_index, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
-        let left_y = rl.get_gamepad_axis_movement(gamepad_index, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
-        let right_x = rl.get_gamepad_axis_movement(gamepad_index, GamepadAxis::GAMEPAD_AXIS_RIGHT_X);
-        let _right_y = rl.get_gamepad_axis_movement(gamepad_index, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y);
-
-        // Movimiento con el stick izquierdo
-        if left_y.abs() > DEADZONE {
-            player.pos.x += -left_y * MOVE_SPEED * player.a.cos();
-            player.pos.y += -left_y * MOVE_SPEED * player.a.sin();
-        }
-        if left_x.abs() > DEADZONE {
-            // Strafe (moverse de lado)
-            player.pos.x += left_x * MOVE_SPEED * player.a.sin();
-            player.pos.y += -left_x * MOVE_SPEED * player.a.cos();
+    pub fn jump(&mut self) {
+        if !self.is_airborne() {
+            self.vz = JUMP_SPEED;
         }
+    }
+}
+
+// Fila de pantalla de un punto a `height` (en alturas de pared) visto a
+// `distance` unidades, con la misma proyección que las paredes. `scale` es
+// la altura en píxeles de una pared a distancia 1.
+pub fn project_height(horizon: f32, eye: f32, height: f32, scale: f32, distance: f32) -> f32 {
+    horizon + (eye - height) * scale / distance
+}
+
+// Giro con teclas; el stick gira según la sensibilidad de las opciones
+const ROTATION_SPEED: f32 = PI; // radianes por segundo
+
+// Un paso de la simulación: giro con teclas o stick y la velocidad del
+// jugador acelerando hacia la dirección pedida durante `dt`. Solo cambia la
+// velocidad; el movimiento con colisiones lo hace la partida, que conoce el
+// laberinto.
+pub fn process_events(player: &mut Player, rl: &RaylibHandle, settings: &Settings, dt: f32) {
+    let invert_x = if settings.invert_x { -1.0 } else { 1.0 };
+    let bindings = &settings.bindings;
+
+    // Teclas y botones cuentan como el eje al máximo
+    let amount = |action: Action| {
+        let digital = if bindings.down(rl, action) { 1.0 } else { 0.0 };
+        bindings.axis(rl, action, settings.deadzone).max(digital)
+    };
+
+    // --- Rotación: teclas a velocidad fija, stick según la sensibilidad ---
+    let turn_key = |action: Action| if bindings.down(rl, action) { 1.0 } else { 0.0 };
+    player.a += turn(turn_key(Action::TurnLeft), turn_key(Action::TurnRight)) * ROTATION_SPEED * dt;
+    let turn_axis = turn(
+        bindings.axis(rl, Action::TurnLeft, settings.deadzone),
+        bindings.axis(rl, Action::TurnRight, settings.deadzone),
+    );
+    player.a += turn_axis * settings.gamepad_sensitivity * invert_x * dt;
 
-        // Rotación con el stick derecho (eje X)
-        if right_x.abs() > DEADZONE {
-            player.a -= right_x * GAMEPAD_SENSITIVITY;
+    // --- Movimiento: avanzar/retroceder y de lado (strafe) ---
+    let forward = amount(Action::MoveForward) - amount(Action::MoveBack);
+    let strafe = amount(Action::StrafeRight) - amount(Action::StrafeLeft);
+    let mut wish = wish_direction(player.a, forward, strafe);
+    // En diagonal no se va más rápido; con el stick a medias sí más lento
+    let input = wish.length();
+    if input > 1.0 {
+        wish /= input;
+    }
+
+    // --- Mirada vertical con teclas o stick ---
+    let invert_y = if settings.invert_y { -1.0 } else { 1.0 };
+    let look_key = |action: Action| if bindings.down(rl, action) { 1.0 } else { 0.0 };
+    let look_axis = |action: Action| bindings.axis(rl, action, settings.deadzone);
+    player.pitch += (look_key(Action::LookUp) - look_key(Action::LookDown)) * PITCH_SPEED * dt;
+    player.pitch += (look_axis(Action::LookUp) - look_axis(Action::LookDown)) * settings.gamepad_sensitivity * 0.5 * invert_y * dt;
+    player.pitch = player.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+    // --- Salto y agacharse: solo cambian la altura de los ojos ---
+    let crouch_target = if bindings.down(rl, Action::Crouch) { 1.0 } else { 0.0 };
+    let crouch_step = CROUCH_RATE * dt;
+    player.crouch += (crouch_target - player.crouch).clamp(-crouch_step, crouch_step);
+    if player.is_airborne() || player.vz > 0.0 {
+        player.vz -= GRAVITY * dt;
+        player.z += player.vz * dt;
+        if player.z <= 0.0 {
+            player.z = 0.0;
+            player.vz = 0.0;
         }
     }
 
+    let movement = &settings.movement;
+    let running = bindings.down(rl, Action::Run) && player.crouch == 0.0;
+    let max_speed = movement.max_speed
+        * if running { movement.run_multiplier } else { 1.0 }
+        * (1.0 - player.crouch * (1.0 - CROUCH_SPEED));
+    let target = wish * max_speed;
+
+    // La velocidad se acerca a la pedida a ritmo de aceleración, o de
+    // fricción cuando no se aprieta nada
+    let rate = if input > 0.0 { movement.acceleration } else { movement.friction };
+    let change = target - player.velocity;
+    let max_change = rate * dt;
+    if change.length() <= max_change {
+        player.velocity = target;
+    } else {
+        player.velocity += change / change.length() * max_change;
+    }
+
     // --- Actualizar plano de cámara ---
     player.update_plane();
 }
+
+// Cuánto cambia el ángulo al girar. La derecha de la vista es el ángulo
+// creciente (las columnas de la pantalla van de `a - fov/2` a `a + fov/2`),
+// así que girar a la derecha suma.
+fn turn(left: f32, right: f32) -> f32 {
+    right - left
+}
+
+// Dirección pedida en el mundo a partir de avanzar y de lado; la derecha de
+// la vista es (-sin a, cos a)
+fn wish_direction(a: f32, forward: f32, strafe: f32) -> Vector2 {
+    Vector2::new(forward * a.cos() - strafe * a.sin(), forward * a.sin() + strafe * a.cos())
+}
+
+// Rotación con el mouse, una vez por frame: el desplazamiento del mouse ya
+// es todo lo que se movió desde el frame anterior
+pub fn process_mouse(player: &mut Player, rl: &RaylibHandle, settings: &Settings) {
+    let invert_x = if settings.invert_x { -1.0 } else { 1.0 };
+    let invert_y = if settings.invert_y { -1.0 } else { 1.0 };
+    let mouse_delta = rl.get_mouse_delta();
+    player.a += mouse_delta.x * settings.mouse_sensitivity * invert_x;
+    player.pitch -= mouse_delta.y * settings.mouse_sensitivity * invert_y;
+    player.pitch = player.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    player.update_plane();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Vector2, expected: Vector2) {
+        assert!((actual - expected).length() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn turning_right_increases_the_angle() {
+        assert_eq!(turn(0.0, 1.0), 1.0);
+        assert_eq!(turn(1.0, 0.0), -1.0);
+        assert_eq!(turn(1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn strafing_right_follows_a_right_turn() {
+        for a in [0.0, 0.7, PI / 2.0, 2.5, -1.2] {
+            // Después de girar un cuarto de vuelta a la derecha, avanzar va
+            // hacia donde antes iba el strafe a la derecha
+            let turned = a + turn(0.0, 1.0) * PI / 2.0;
+            assert_near(wish_direction(a, 0.0, 1.0), wish_direction(turned, 1.0, 0.0));
+            assert_near(wish_direction(a, 0.0, -1.0), -wish_direction(turned, 1.0, 0.0));
+            assert_near(wish_direction(a, 1.0, 0.0), Vector2::new(a.cos(), a.sin()));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use raylib::prelude::*;
 use crate::framebuffer::Framebuffer;
-use crate::player::Player;
+use crate::maze::LevelMeta;
+use crate::player::{project_height, Player};
 use crate::textures::TextureManager;
 
 #[derive(Clone)]
@@ -46,12 +47,16 @@ impl Sprite {
 pub fn render_sprites(
     framebuffer: &mut Framebuffer,
     player: &Player,
-    sprites: &Vec<Sprite>,
-    z_buffer: &Vec<f32>,
+    sprites: &[Sprite],
+    z_buffer: &[f32],
+    meta: &LevelMeta,
 ) {
     let num_rays = framebuffer.width as usize;
     let hh = framebuffer.height as f32 / 2.0;
     let distance_to_projection_plane = 70.0;
+    let scale = hh * distance_to_projection_plane;
+    let horizon = player.horizon(framebuffer.height);
+    let eye = player.eye_height();
 
     for sprite in sprites.iter() {
         let dx = sprite.pos.x - player.pos.x;
@@ -59,7 +64,7 @@ pub fn render_sprites(
         let sprite_dist = (dx * dx + dy * dy).sqrt().max(0.0001);
 
         let angle_to_sprite = dy.atan2(dx);
-        let mut angle_diff = angle_to_sprite - player.a;
+        let mut angle_diff = angle_to_sprite - player.view_angle();
         while angle_diff > std::f32::consts::PI {
             angle_diff -= 2.0 * std::f32::consts::PI;
         }
@@ -74,7 +79,9 @@ pub fn render_sprites(
         let screen_x = ((angle_diff + (player.fov / 2.0)) / player.fov) * (num_rays as f32);
         let sprite_screen_height = ((hh / sprite_dist) * distance_to_projection_plane).abs();
         let sprite_half_h = (sprite_screen_height / 2.0) as i32;
-        let sprite_center_y = hh as i32;
+        // Los sprites van del piso a la altura de una pared, como las paredes
+        let top = project_height(horizon, eye, 1.0, scale, sprite_dist) as i32;
+        let bottom = project_height(horizon, eye, 0.0, scale, sprite_dist) as i32;
 
         let tex_w = sprite.width;
         let tex_h = sprite.height;
@@ -91,9 +98,6 @@ pub fn render_sprites(
             let rel = (screen_col - left) as f32 / ((right - left).max(1) as f32);
             let tex_x = (rel * tex_w as f32) as i32;
 
-            let top = sprite_center_y - sprite_half_h;
-            let bottom = sprite_center_y + sprite_half_h;
-
             for screen_y in top.max(0)..=bottom.min((framebuffer.height as i32) - 1) {
                 let v_rel = (screen_y - top) as f32 / ((bottom - top).max(1) as f32);
                 let tex_y = (v_rel * tex_h as f32) as i32;
@@ -108,7 +112,7 @@ pub fn render_sprites(
                     continue;
                 }
 
-                framebuffer.set_current_color(color);
+                framebuffer.set_current_color(meta.fogged(color, sprite_dist));
                 framebuffer.set_pixel(screen_col as u32, screen_y as u32);
             }
         }
@@ -0,0 +1,44 @@
+// storage.rs
+//
+// Archivos del usuario: las opciones van en el directorio de configuración
+// y el progreso en el de datos, los dos dentro de una carpeta `raycaster`.
+// En Windows ambos son APPDATA; en el resto se siguen las variables XDG con
+// sus valores por defecto.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "raycaster";
+
+#[derive(Clone, Copy)]
+pub enum UserDir {
+    Config, // XDG_CONFIG_HOME o ~/.config
+    Data,   // XDG_DATA_HOME o ~/.local/share
+}
+
+fn user_dir(kind: UserDir) -> Option<PathBuf> {
+    let env = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if cfg!(windows) {
+        return env("APPDATA");
+    }
+    let (variable, default): (&str, &[&str]) = match kind {
+        UserDir::Config => ("XDG_CONFIG_HOME", &[".config"]),
+        UserDir::Data => ("XDG_DATA_HOME", &[".local", "share"]),
+    };
+    env(variable).or_else(|| env("HOME").map(|home| default.iter().fold(home, |path, part| path.join(part))))
+}
+
+// Ruta de `name` en la carpeta del juego, o None si no hay directorio
+pub fn user_file(kind: UserDir, name: &str) -> Option<PathBuf> {
+    user_dir(kind).map(|dir| dir.join(APP_DIR).join(name))
+}
+
+// Escribe el archivo creando los directorios que falten; si no se puede,
+// solo se avisa: el juego sigue sin guardar. `what` es lo que se guarda,
+// para el mensaje.
+pub fn write_user_file(path: &Path, text: &str, what: &str) {
+    let result = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(path, text));
+    if let Err(e) = result {
+        eprintln!("No se pudo guardar {} en {}: {}", what, path.display(), e);
+    }
+}
@@ -2,8 +2,18 @@ use raylib::prelude::*;
 use std::collections::HashMap;
 use std::slice;
 
+// Claves reservadas para las texturas de cielo y piso del nivel actual
+pub const SKY_TEXTURE: char = '~';
+pub const FLOOR_TEXTURE: char = '_';
+// Arma en primera persona y proyectiles del lanzador
+pub const WEAPON_TEXTURE: char = '!';
+pub const PROJECTILE_TEXTURE: char = '*';
+
 pub struct TextureManager {
     pub(crate) images: HashMap<char, Image>, // Imágenes en CPU para lectura de píxeles
+    // Lo que reemplazaron las texturas del nivel actual (None si la clave
+    // no existía), para volver a las de base al cambiar de nivel
+    replaced: HashMap<char, Option<Image>>,
 }
 
 impl TextureManager {
@@ -16,6 +26,7 @@ impl TextureManager {
             ('+', "assets/wall.png"),
             ('-', "assets/wall.png"),
             ('|', "assets/wall.png"),
+            ('D', "assets/wall.png"),
             ('p', "assets/pokeball.jpg"),
 
             // Sprites
@@ -35,7 +46,45 @@ impl TextureManager {
             images.insert(ch, image);
         }
 
-        TextureManager { images }
+        // Enemigos: si no hay imagen propia se ven como un bloque rojo
+        let mut enemy = Image::load_image("assets/enemy.png")
+            .unwrap_or_else(|_| Image::gen_image_color(64, 64, Color::MAROON));
+        enemy.set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        images.insert('X', enemy);
+
+        // El arma puede ser una tira de cuadros cuadrados: el primero es el de
+        // reposo y los demás la animación de disparo
+        let mut weapon = Image::load_image("assets/weapon.png")
+            .unwrap_or_else(|_| Image::gen_image_color(64, 64, Color::DARKGRAY));
+        weapon.set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        images.insert(WEAPON_TEXTURE, weapon);
+
+        let mut projectile = Image::load_image("assets/projectile.png")
+            .unwrap_or_else(|_| Image::gen_image_color(16, 16, Color::ORANGE));
+        projectile.set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        images.insert(PROJECTILE_TEXTURE, projectile);
+
+        TextureManager { images, replaced: HashMap::new() }
+    }
+
+    // Carga (o reemplaza) una textura declarada por el nivel; dura hasta
+    // el próximo `reset_level`
+    pub fn load(&mut self, ch: char, path: &str) -> Result<(), String> {
+        let mut image = Image::load_image(path).map_err(|e| e.to_string())?;
+        image.set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        let previous = self.images.insert(ch, image);
+        self.replaced.entry(ch).or_insert(previous);
+        Ok(())
+    }
+
+    // Deshace las texturas del nivel anterior y vuelve a las de base
+    pub fn reset_level(&mut self) {
+        for (ch, original) in self.replaced.drain() {
+            match original {
+                Some(image) => self.images.insert(ch, image),
+                None => self.images.remove(&ch),
+            };
+        }
     }
 
     pub fn get_pixel_color(&self, ch: char, tx: u32, ty: u32) -> Color {
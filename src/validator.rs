@@ -0,0 +1,370 @@
+// validator.rs
+//
+// Comprueba que un nivel se pueda completar: que desde cada spawn se llegue
+// a todos los coleccionables y a alguna salida, y qué zonas del mapa quedan
+// aisladas. La búsqueda es un flood fill sobre una grilla de puntos más fina
+// que la del laberinto, donde un punto es transitable si un círculo del
+// radio de colisión del jugador cabe ahí sin tocar paredes. Las puertas
+// cuentan como transitables, porque el jugador las puede abrir.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::cell::Cell;
+use crate::maze::Level;
+use crate::pathfinding::{PathOptions, Pathfinder};
+
+// Puntos de muestra por celda en cada eje
+const SAMPLES: usize = 4;
+
+// Paredes de verdad: una puerta se abre y deja pasar
+fn blocks(cell: &Cell) -> bool {
+    cell.is_solid() && !matches!(cell, Cell::Door)
+}
+
+#[derive(Debug)]
+pub enum Issue {
+    NoSpawn,
+    SpawnBlocked { name: String },
+    UnreachablePickup { spawn: String, kind: char, col: usize, row: usize },
+    UnreachableExit { spawn: String, col: usize, row: usize },
+    NoReachableExit { spawn: String },
+    IsolatedArea { col: usize, row: usize, cells: usize },
+    MissingSpawn { name: String, source: String },
+}
+
+impl Issue {
+    // Los problemas fatales hacen imposible terminar el nivel
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Issue::NoSpawn | Issue::SpawnBlocked { .. } | Issue::UnreachablePickup { .. } | Issue::NoReachableExit { .. }
+        )
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::NoSpawn => write!(f, "error: el nivel no tiene punto de aparición"),
+            Issue::SpawnBlocked { name } => {
+                write!(f, "error: el jugador no cabe en el spawn '{}'", name)
+            }
+            Issue::UnreachablePickup { spawn, kind, col, row } => write!(
+                f,
+                "error: el coleccionable '{}' (fila {}, columna {}) no se alcanza desde el spawn '{}'",
+                kind, row + 1, col + 1, spawn
+            ),
+            Issue::UnreachableExit { spawn, col, row } => write!(
+                f,
+                "aviso: la salida de la fila {}, columna {} no se alcanza desde el spawn '{}'",
+                row + 1, col + 1, spawn
+            ),
+            Issue::NoReachableExit { spawn } => {
+                write!(f, "error: ninguna salida se alcanza desde el spawn '{}'", spawn)
+            }
+            Issue::IsolatedArea { col, row, cells } => write!(
+                f,
+                "aviso: zona aislada de {} celdas desde la fila {}, columna {}",
+                cells, row + 1, col + 1
+            ),
+            Issue::MissingSpawn { name, source } => {
+                write!(f, "aviso: {} apunta al spawn '{}', que no existe", source, name)
+            }
+        }
+    }
+}
+
+struct Sampler<'a> {
+    level: &'a Level,
+    block_size: f32,
+    radius: f32,
+    width: usize,  // puntos por fila
+    height: usize,
+}
+
+impl Sampler<'_> {
+    fn step(&self) -> f32 {
+        self.block_size / SAMPLES as f32
+    }
+
+    fn point(&self, index: usize) -> (f32, f32) {
+        let step = self.step();
+        ((index % self.width) as f32 * step + step / 2.0, (index / self.width) as f32 * step + step / 2.0)
+    }
+
+    fn cell_of(&self, index: usize) -> (usize, usize) {
+        ((index % self.width) / SAMPLES, (index / self.width) / SAMPLES)
+    }
+
+    // ¿Choca el rectángulo [x0, x1] x [y0, y1] engordado por el radio con
+    // alguna pared? Un punto o un tramo recto son rectángulos degenerados.
+    fn blocked(&self, x0: f32, x1: f32, y0: f32, y1: f32) -> bool {
+        let bs = self.block_size;
+        let first_col = ((x0 - self.radius) / bs).floor().max(0.0) as usize;
+        let first_row = ((y0 - self.radius) / bs).floor().max(0.0) as usize;
+        let last_col = ((x1 + self.radius) / bs).floor() as usize;
+        let last_row = ((y1 + self.radius) / bs).floor() as usize;
+
+        for row in first_row..=last_row {
+            for col in first_col..=last_col {
+                let solid = self.level.maze.get(row).and_then(|cells| cells.get(col)).is_none_or(blocks);
+                if !solid {
+                    continue;
+                }
+                let (cx0, cy0) = (col as f32 * bs, row as f32 * bs);
+                let dx = (cx0 - x1).max(x0 - (cx0 + bs)).max(0.0);
+                let dy = (cy0 - y1).max(y0 - (cy0 + bs)).max(0.0);
+                if (dx * dx + dy * dy).sqrt() <= self.radius {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn walkable(&self, index: usize) -> bool {
+        let (x, y) = self.point(index);
+        !self.blocked(x, x, y, y)
+    }
+
+    // Puntos alcanzables desde el centro de la celda (col, row)
+    fn flood(&self, col: usize, row: usize) -> Option<Vec<bool>> {
+        let center_x = (col as f32 + 0.5) * self.block_size;
+        let center_y = (row as f32 + 0.5) * self.block_size;
+        if self.blocked(center_x, center_x, center_y, center_y) {
+            return None;
+        }
+
+        let mut reached = vec![false; self.width * self.height];
+        let mut queue = VecDeque::new();
+        for dy in 0..SAMPLES {
+            for dx in 0..SAMPLES {
+                let index = (row * SAMPLES + dy) * self.width + col * SAMPLES + dx;
+                if self.walkable(index) {
+                    reached[index] = true;
+                    queue.push_back(index);
+                }
+            }
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let (x, y) = self.point(index);
+            let (px, py) = (index % self.width, index / self.width);
+            let mut neighbors = Vec::with_capacity(4);
+            if px > 0 { neighbors.push(index - 1); }
+            if px + 1 < self.width { neighbors.push(index + 1); }
+            if py > 0 { neighbors.push(index - self.width); }
+            if py + 1 < self.height { neighbors.push(index + self.width); }
+
+            for next in neighbors {
+                if reached[next] {
+                    continue;
+                }
+                // Se comprueba el tramo completo para no atravesar esquinas
+                let (nx, ny) = self.point(next);
+                if !self.blocked(x.min(nx), x.max(nx), y.min(ny), y.max(ny)) {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        Some(reached)
+    }
+}
+
+// `radius` es el radio de colisión del jugador y `pickup_range` la distancia
+// a la que recoge un coleccionable, ambos en unidades del mundo
+pub fn validate(level: &Level, block_size: usize, radius: f32, pickup_range: f32) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if level.spawns.is_empty() {
+        issues.push(Issue::NoSpawn);
+        return issues;
+    }
+
+    let sampler = Sampler {
+        level,
+        block_size: block_size as f32,
+        radius,
+        width: level.width * SAMPLES,
+        height: level.height * SAMPLES,
+    };
+
+    let mut pickups = Vec::new();
+    for (row, cells) in level.maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if let Cell::Pickup { kind } = cell {
+                pickups.push((kind, col, row));
+            }
+        }
+    }
+
+    let mut reached_cells = vec![vec![false; level.width]; level.height];
+    for spawn in &level.spawns {
+        let Some(reached) = sampler.flood(spawn.col, spawn.row) else {
+            issues.push(Issue::SpawnBlocked { name: spawn.name.clone() });
+            continue;
+        };
+        for index in (0..reached.len()).filter(|&index| reached[index]) {
+            let (col, row) = sampler.cell_of(index);
+            reached_cells[row][col] = true;
+        }
+
+        for &(kind, col, row) in &pickups {
+            let target_x = (col as f32 + 0.5) * sampler.block_size;
+            let target_y = (row as f32 + 0.5) * sampler.block_size;
+            let collected = (0..reached.len()).filter(|&index| reached[index]).any(|index| {
+                let (x, y) = sampler.point(index);
+                ((x - target_x).powi(2) + (y - target_y).powi(2)).sqrt() < pickup_range
+            });
+            if !collected {
+                issues.push(Issue::UnreachablePickup { spawn: spawn.name.clone(), kind, col, row });
+            }
+        }
+
+        let mut any_exit = false;
+        for exit in &level.exits {
+            let inside = (0..reached.len()).any(|index| reached[index] && sampler.cell_of(index) == (exit.col, exit.row));
+            if inside {
+                any_exit = true;
+            } else {
+                issues.push(Issue::UnreachableExit { spawn: spawn.name.clone(), col: exit.col, row: exit.row });
+            }
+        }
+        if !level.exits.is_empty() && !any_exit {
+            issues.push(Issue::NoReachableExit { spawn: spawn.name.clone() });
+        }
+    }
+
+    // Zonas transitables a las que no se llega desde ningún spawn
+    let mut visited = reached_cells;
+    let mut pathfinder = Pathfinder::for_maze(&level.maze);
+    for row in 0..level.height {
+        for col in 0..level.width {
+            if visited[row][col] || blocks(&level.maze[row][col]) {
+                continue;
+            }
+            let area = pathfinder.reachable(&level.maze, (col, row), PathOptions { doors: true, ..Default::default() });
+            let mut cells = 0;
+            for (visited_row, area_row) in visited.iter_mut().zip(&area) {
+                for (seen, &inside) in visited_row.iter_mut().zip(area_row) {
+                    if inside && !*seen {
+                        *seen = true;
+                        cells += 1;
+                    }
+                }
+            }
+            issues.push(Issue::IsolatedArea { col, row, cells });
+        }
+    }
+
+    for trigger in level.triggers.iter().filter(|trigger| trigger.action == "teleport") {
+        if let Some(target) = &trigger.target
+            && !level.spawns.iter().any(|spawn| &spawn.name == target)
+        {
+            issues.push(Issue::MissingSpawn { name: target.clone(), source: format!("el trigger '{}'", trigger.name) });
+        }
+    }
+
+    issues
+}
+
+// Las salidas llevan a un spawn del nivel `next`; si no existe se entra por el de inicio
+pub fn check_exit_targets(level: &Level, next: &Level) -> Vec<Issue> {
+    level
+        .exits
+        .iter()
+        .filter_map(|exit| exit.target_spawn.as_ref().map(|target| (exit, target)))
+        .filter(|(_, target)| !next.spawns.iter().any(|spawn| &spawn.name == *target))
+        .map(|(exit, target)| Issue::MissingSpawn {
+            name: target.clone(),
+            source: format!("la salida de la fila {}, columna {}", exit.row + 1, exit.col + 1),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::parse_level;
+    use crate::player::PLAYER_RADIUS;
+
+    const BS: usize = 100;
+    const PICKUP_RANGE: f32 = 80.0;
+
+    fn level(rows: &[&str]) -> Level {
+        parse_level(&rows.join("\n"), "prueba").unwrap()
+    }
+
+    fn check(rows: &[&str]) -> Vec<Issue> {
+        validate(&level(rows), BS, PLAYER_RADIUS, PICKUP_RANGE)
+    }
+
+    #[test]
+    fn reports_an_unreachable_pickup() {
+        let issues = check(&["+++++++", "+S +A +", "+  +  +", "+++++++"]);
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            Issue::UnreachablePickup { spawn, kind: 'A', col: 4, row: 1 } if spawn == "inicio"
+        )));
+        assert!(issues.iter().any(Issue::is_fatal));
+    }
+
+    #[test]
+    fn the_player_does_not_fit_through_a_narrow_corridor() {
+        let rooms = ["+++++++++", "+   +   +", "+ S   A +", "+   +   +", "+++++++++"];
+        assert!(check(&rooms).is_empty());
+        // Con celdas de 30 el hueco entre las salas es más angosto que el jugador
+        let issues = validate(&level(&rooms), 30, PLAYER_RADIUS, 10.0);
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::UnreachablePickup { kind: 'A', .. })));
+
+        let issues = validate(&level(&["+++++", "+ S +", "+++++"]), 30, PLAYER_RADIUS, 10.0);
+        assert!(matches!(issues.as_slice(), [Issue::SpawnBlocked { name }, ..] if name == "inicio"));
+    }
+
+    #[test]
+    fn counts_the_cells_of_isolated_areas() {
+        let issues = check(&["++++++++++", "+S +   + +", "+  +   +++", "++++++++++"]);
+        let areas: Vec<(usize, usize, usize)> = issues
+            .iter()
+            .filter_map(|issue| match issue {
+                Issue::IsolatedArea { col, row, cells } => Some((*col, *row, *cells)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(areas, vec![(4, 1, 6), (8, 1, 1)]);
+        assert!(!issues.iter().any(Issue::is_fatal));
+    }
+
+    #[test]
+    fn doors_count_as_passable() {
+        assert!(check(&["+++++++", "+S D A+", "+++++++"]).is_empty());
+        let walled = check(&["+++++++", "+S + A+", "+++++++"]);
+        assert!(walled.iter().any(|issue| matches!(issue, Issue::UnreachablePickup { .. })));
+    }
+
+    #[test]
+    fn one_reachable_exit_is_enough() {
+        let issues = check(&["+++++++++", "+S E+ E +", "+++++++++"]);
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::UnreachableExit { col: 6, row: 1, .. })));
+        assert!(!issues.iter().any(|issue| matches!(issue, Issue::UnreachableExit { col: 3, .. })));
+        assert!(!issues.iter().any(|issue| matches!(issue, Issue::NoReachableExit { .. })));
+        assert!(!issues.iter().any(Issue::is_fatal));
+
+        let issues = check(&["+++++++", "+S +E +", "+++++++"]);
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::UnreachableExit { col: 4, row: 1, .. })));
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::NoReachableExit { spawn } if spawn == "inicio")));
+        assert!(issues.iter().any(Issue::is_fatal));
+    }
+
+    #[test]
+    fn reports_exits_to_a_missing_spawn() {
+        let current = level(&["[legend]", "F = \"exit 3\"", "[grid]", "+++++", "+S F+", "+++++"]);
+        let issues = check_exit_targets(&current, &level(&["+++++", "+S  +", "+++++"]));
+        assert!(matches!(
+            issues.as_slice(),
+            [Issue::MissingSpawn { name, source }] if name == "3" && source == "la salida de la fila 2, columna 4"
+        ));
+        assert!(check_exit_targets(&current, &level(&["+++++", "+S 3+", "+++++"])).is_empty());
+    }
+}
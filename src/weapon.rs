@@ -0,0 +1,237 @@
+// weapon.rs
+//
+// Armas del jugador: la pistola resuelve el disparo al instante con un rayo
+// que corta en la primera pared o en el primer enemigo que cruza, y el
+// lanzador dispara proyectiles que viajan por el mundo como sprites, chocan
+// con las paredes como círculos chicos y dañan al enemigo que tocan. El arma
+// se dibuja en primera persona encima de la escena, con retroceso y fogonazo
+// al disparar.
+
+use raylib::prelude::*;
+
+use crate::enemy::{Enemy, HIT_RADIUS};
+use crate::framebuffer::Framebuffer;
+use crate::collision::overlaps;
+use crate::maze::{is_wall, Maze};
+use crate::player::Player;
+use crate::sprites::Sprite;
+use crate::textures::{TextureManager, PROJECTILE_TEXTURE, WEAPON_TEXTURE};
+
+const HITSCAN_RANGE: f32 = 2000.0;
+const PROJECTILE_SPEED: f32 = 600.0; // unidades del mundo por segundo
+const PROJECTILE_LIFETIME: f32 = 4.0;
+// Largo máximo de cada subpaso del proyectil, para no atravesar paredes
+const PROJECTILE_STEP: f32 = 10.0;
+const PROJECTILE_RADIUS: f32 = 6.0; // explota al rozar la pared
+
+const FIRE_ANIMATION: f32 = 0.15; // segundos de retroceso y fogonazo
+pub const OVERLAY_SIZE: u32 = 256; // píxeles de pantalla del arma
+const RECOIL: f32 = 24.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeaponKind {
+    Pistol,   // hitscan
+    Launcher, // proyectiles
+}
+
+impl WeaponKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeaponKind::Pistol => "Pistola",
+            WeaponKind::Launcher => "Lanzador",
+        }
+    }
+
+    fn damage(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 20.0,
+            WeaponKind::Launcher => 45.0,
+        }
+    }
+
+    // Segundos entre disparos
+    fn cooldown(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 0.35,
+            WeaponKind::Launcher => 0.9,
+        }
+    }
+}
+
+pub struct Projectile {
+    pub sprite: Sprite,
+    velocity: Vector2,
+    damage: f32,
+    age: f32,
+}
+
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub projectiles: Vec<Projectile>,
+    cooldown: f32,
+    firing: f32, // tiempo restante de la animación de disparo
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Weapon::new()
+    }
+}
+
+impl Weapon {
+    pub fn new() -> Self {
+        Weapon { kind: WeaponKind::Pistol, projectiles: Vec::new(), cooldown: 0.0, firing: 0.0 }
+    }
+
+    pub fn select(&mut self, kind: WeaponKind) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.cooldown = 0.0;
+            self.firing = 0.0;
+        }
+    }
+
+    // Dispara si el arma está lista. Devuelve true si salió el disparo.
+    pub fn fire(
+        &mut self,
+        player: &Player,
+        maze: &Maze,
+        enemies: &mut [Enemy],
+        block_size: usize,
+        texture_cache: &TextureManager,
+    ) -> bool {
+        if self.cooldown > 0.0 {
+            return false;
+        }
+        self.cooldown = self.kind.cooldown();
+        self.firing = FIRE_ANIMATION;
+
+        let direction = Vector2::new(player.a.cos(), player.a.sin());
+        match self.kind {
+            WeaponKind::Pistol => {
+                if let Some(index) = hitscan(maze, player.pos, direction, enemies, block_size) {
+                    enemies[index].hit(self.kind.damage(), player.pos);
+                }
+            }
+            WeaponKind::Launcher => {
+                if let Some(sprite) = Sprite::from_char(PROJECTILE_TEXTURE, player.pos.x, player.pos.y, texture_cache) {
+                    self.projectiles.push(Projectile {
+                        sprite,
+                        velocity: direction * PROJECTILE_SPEED,
+                        damage: self.kind.damage(),
+                        age: 0.0,
+                    });
+                }
+            }
+        }
+        true
+    }
+
+    // Avanza los proyectiles y los descarta al chocar o al vencer
+    pub fn update(&mut self, dt: f32, maze: &Maze, enemies: &mut [Enemy], block_size: usize) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        self.firing = (self.firing - dt).max(0.0);
+
+        self.projectiles.retain_mut(|projectile| {
+            projectile.age += dt;
+            if projectile.age > PROJECTILE_LIFETIME {
+                return false;
+            }
+
+            let travel = projectile.velocity * dt;
+            let steps = (travel.length() / PROJECTILE_STEP).ceil().max(1.0) as usize;
+            for _ in 0..steps {
+                let pos = projectile.sprite.pos + travel / steps as f32;
+                if overlaps(maze, pos, PROJECTILE_RADIUS, block_size) {
+                    return false;
+                }
+                projectile.sprite.pos = pos;
+                projectile.sprite.x = pos.x;
+                projectile.sprite.y = pos.y;
+
+                if let Some(enemy) = enemies.iter_mut().find(|enemy| (enemy.sprite.pos - pos).length() < HIT_RADIUS) {
+                    enemy.hit(projectile.damage, pos - projectile.velocity.normalized() * HIT_RADIUS);
+                    return false;
+                }
+            }
+            true
+        });
+    }
+
+    // Arma en primera persona, abajo al centro. Se dibuja después de
+    // `render_sprites` para que quede delante de todo lo del mundo.
+    pub fn draw_overlay(&self, framebuffer: &mut Framebuffer, texture_cache: &TextureManager) {
+        let Some(image) = texture_cache.images.get(&WEAPON_TEXTURE) else { return };
+        let frame_size = image.height.max(1) as u32;
+        let frames = (image.width as u32 / frame_size).max(1);
+
+        // Progreso de la animación: 0 en reposo, 1 justo al disparar
+        let progress = self.firing / FIRE_ANIMATION;
+        let frame = if progress > 0.0 && frames > 1 {
+            1 + ((1.0 - progress) * (frames - 1) as f32) as u32
+        } else {
+            0
+        }
+        .min(frames - 1);
+
+        // En framebuffers chicos el arma se achica para que entre entera
+        let size = OVERLAY_SIZE.min(framebuffer.width).min(framebuffer.height);
+        let recoil = ((RECOIL * progress) as u32).min(size);
+        let left = framebuffer.width.saturating_sub(size) / 2;
+        let top = framebuffer.height.saturating_sub(size) + recoil;
+
+        if progress > 0.0 {
+            draw_flash(framebuffer, left + size / 2, top, progress);
+        }
+
+        for y in 0..size - recoil {
+            for x in 0..size {
+                let tx = frame * frame_size + x * frame_size / size;
+                let ty = y * frame_size / size;
+                let color = texture_cache.get_pixel_color(WEAPON_TEXTURE, tx, ty);
+                if color.a > 0 {
+                    framebuffer.set_current_color(color);
+                    framebuffer.set_pixel(left + x, top + y);
+                }
+            }
+        }
+    }
+}
+
+// Fogonazo en la boca del arma; se achica a medida que termina la animación
+fn draw_flash(framebuffer: &mut Framebuffer, cx: u32, cy: u32, progress: f32) {
+    let radius = (20.0 * progress) as i32;
+    framebuffer.set_current_color(Color::YELLOW);
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy <= radius * radius {
+                framebuffer.set_pixel((cx as i32 + dx) as u32, (cy as i32 + dy) as u32);
+            }
+        }
+    }
+}
+
+// Rayo desde `origin` en `direction` (normalizada): devuelve el enemigo más
+// cercano que cruza antes de la primera pared
+pub fn hitscan(maze: &Maze, origin: Vector2, direction: Vector2, enemies: &[Enemy], block_size: usize) -> Option<usize> {
+    let mut wall_distance = 0.0;
+    while wall_distance < HITSCAN_RANGE {
+        let point = origin + direction * wall_distance;
+        if is_wall(point.x, point.y, maze, block_size) {
+            break;
+        }
+        wall_distance += 1.0;
+    }
+
+    enemies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, enemy)| {
+            let to_enemy = enemy.sprite.pos - origin;
+            let along = to_enemy.dot(direction);
+            let across = (to_enemy - direction * along).length();
+            (along > 0.0 && along < wall_distance && across < HIT_RADIUS).then_some((index, along))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}